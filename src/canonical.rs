@@ -0,0 +1,204 @@
+use std::cmp::Ordering;
+use indexmap::IndexMap;
+use sha2::{Digest, Sha256};
+use crate::value::Value;
+
+// The hash algorithms of the digest command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Sha256,
+}
+
+impl DigestAlgorithm {
+    pub fn for_str(algorithm: &str) -> Result<Self, ()> {
+        match algorithm {
+            "sha256" => Ok(DigestAlgorithm::Sha256),
+            _ => Err(()),
+        }
+    }
+}
+
+// The largest integer that an IEEE 754 double holds exactly, as of I-JSON (RFC 7493).
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+// Compares 2 given keys by their UTF-16 code units, as of RFC 8785.
+fn compare_keys(left: &str, right: &str) -> Ordering {
+    left.encode_utf16().cmp(right.encode_utf16())
+}
+
+// Creates a new Value whose Object keys are recursively sorted.
+pub fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Array(a) => Value::Array(a.iter().map(sort_keys).collect()),
+        Value::Object(o) => {
+            let mut entries: Vec<(&String, &Value)> = o.iter().collect();
+            entries.sort_by(|(l, _), (r, _)| compare_keys(l, r));
+            let new_obj: IndexMap<String, Value> = entries.into_iter()
+                .map(|(k, v)| (k.clone(), sort_keys(v)))
+                .collect();
+
+            Value::Object(new_obj)
+        },
+        _ => value.clone(),
+    }
+}
+
+// Formats a given number as ECMAScript does, as of RFC 8785.
+fn format_number(number: f64) -> Result<String, String> {
+    if !number.is_finite() {
+        return Err(format!("{} has no canonical form", number));
+    }
+    if number == 0.0 {
+        // Including -0.
+        return Ok("0".to_owned());
+    }
+
+    // The shortest digits that round-trip, and the position of the decimal point.
+    let sci = format!("{:e}", number.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    let mut sb = String::new();
+    if number < 0.0 {
+        sb.push('-');
+    }
+    if k <= n && n <= 21 {
+        sb.push_str(&digits);
+        sb.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        sb.push_str(&digits[..n as usize]);
+        sb.push('.');
+        sb.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        sb.push_str("0.");
+        sb.push_str(&"0".repeat(-n as usize));
+        sb.push_str(&digits);
+    } else {
+        sb.push_str(&digits[..1]);
+        if k > 1 {
+            sb.push('.');
+            sb.push_str(&digits[1..]);
+        }
+        sb.push('e');
+        sb.push(if n > 0 { '+' } else { '-' });
+        sb.push_str(&(n - 1).abs().to_string());
+    }
+
+    Ok(sb)
+}
+
+// Writes the canonical JSON representation of a given Value.
+fn write_canonical_json(value: &Value, sb: &mut String) -> Result<(), String> {
+    match value {
+        Value::Nil => sb.push_str("null"),
+        Value::Boolean(b) => sb.push_str(if *b { "true" } else { "false" }),
+        // Numbers are IEEE 754 doubles, so the larger integers can't be written without losing precision.
+        Value::Integer(i) if i.unsigned_abs() > MAX_SAFE_INTEGER as u64 => return Err(format!("integer {} has no canonical form", i)),
+        Value::Integer(i) => sb.push_str(&format_number(*i as f64)?),
+        Value::Float(f) => sb.push_str(&format_number(*f)?),
+        Value::String(s) => sb.push_str(&serde_json::to_string(s).unwrap()),
+        Value::Array(a) => {
+            sb.push('[');
+            for (idx, v) in a.iter().enumerate() {
+                if idx > 0 {
+                    sb.push(',');
+                }
+                write_canonical_json(v, sb)?;
+            } // for
+            sb.push(']');
+        },
+        Value::Object(o) => {
+            let mut entries: Vec<(&String, &Value)> = o.iter().collect();
+            entries.sort_by(|(l, _), (r, _)| compare_keys(l, r));
+            sb.push('{');
+            for (idx, (k, v)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    sb.push(',');
+                }
+                sb.push_str(&serde_json::to_string(k).unwrap());
+                sb.push(':');
+                write_canonical_json(v, sb)?;
+            } // for
+            sb.push('}');
+        },
+    }
+
+    Ok(())
+}
+
+// Returns the canonical JSON representation of a given Value, as of RFC 8785.
+pub fn to_canonical_json(value: &Value) -> Result<String, String> {
+    let mut sb = String::new();
+    write_canonical_json(value, &mut sb)?;
+
+    Ok(sb)
+}
+
+// Returns the hexadecimal digest of the canonical JSON representation of a given Value.
+pub fn digest(value: &Value, algorithm: DigestAlgorithm) -> Result<String, String> {
+    let json = to_canonical_json(value)?;
+    match algorithm {
+        DigestAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(json.as_bytes());
+            Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod sort_keys {
+        use super::*;
+
+        #[test]
+        fn it_sorts_keys_recursively() {
+            let res = sort_keys(&new_value(r#"{"b":[{"d":1,"c":2}],"a":{"f":1,"e":2}}"#));
+            assert_eq!(serde_json::to_string(&res).unwrap(), r#"{"a":{"e":2,"f":1},"b":[{"c":2,"d":1}]}"#);
+        }
+    }
+
+    mod to_canonical_json {
+        use super::*;
+
+        #[test]
+        fn it_formats_numbers() {
+            let res = to_canonical_json(&new_value("[0, -0.0, 1, -1.5, 1e21, 1e20, 1e-7, 0.000001, 5e-324, 9007199254740992.0]"));
+            assert_eq!(res, Ok("[0,0,1,-1.5,1e+21,100000000000000000000,1e-7,0.000001,5e-324,9007199254740992]".to_owned()));
+            let res = to_canonical_json(&Value::Float("333333333.33333329".parse().unwrap()));
+            assert_eq!(res, Ok("333333333.3333333".to_owned()));
+            assert!(to_canonical_json(&Value::Float(f64::NAN)).is_err());
+        }
+
+        #[test]
+        fn it_fails_on_unsafe_integers() {
+            let res = to_canonical_json(&new_value("[9007199254740991, -9007199254740991]"));
+            assert_eq!(res, Ok("[9007199254740991,-9007199254740991]".to_owned()));
+            assert_eq!(to_canonical_json(&Value::Integer(9007199254740993)), Err("integer 9007199254740993 has no canonical form".to_owned()));
+            assert!(to_canonical_json(&Value::Integer(i64::MIN)).is_err());
+        }
+
+        #[test]
+        fn it_sorts_keys_by_utf16_code_units() {
+            let res = to_canonical_json(&new_value("{\"\u{fb33}\":1,\"\u{1f600}\":2,\"a\\nb\":3,\"1\":4}"));
+            assert_eq!(res, Ok("{\"1\":4,\"a\\nb\":3,\"\u{1f600}\":2,\"\u{fb33}\":1}".to_owned()));
+        }
+    }
+
+    mod digest {
+        use super::*;
+
+        #[test]
+        fn it_ignores_key_order_and_whitespace() {
+            let left = digest(&new_value(r#"{"a": 1, "b": [true, null]}"#), DigestAlgorithm::Sha256);
+            let right = digest(&new_value(r#"{"b":[true,null],"a":1.0}"#), DigestAlgorithm::Sha256);
+            assert_eq!(left, right);
+            assert_eq!(left.unwrap().len(), 64);
+        }
+    }
+}
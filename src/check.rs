@@ -0,0 +1,399 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use crate::command;
+use crate::patch;
+use crate::unit::FileFormat;
+use crate::value::Value;
+use crate::yaml;
+
+const BOM: char = '\u{FEFF}';
+
+// Records the duplicate keys of the Object tuples, since the last one silently wins.
+const LUA_DUPLICATE_KEYS: &str = r#"
+_duplicate_keys = {}
+local object_new = Object.new
+function Object:new(init)
+    if type(init) == 'table' then
+        local seen = {}
+        for _, tuple in ipairs(init) do
+            local k = tuple[1]
+            if seen[k] then
+                table.insert(_duplicate_keys, k)
+            end
+            seen[k] = true
+        end
+    end
+
+    return object_new(self, init)
+end
+"#;
+
+// The output formats of a check report.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    // One line per problem, on stderr.
+    #[default]
+    Text,
+    // A JSON array of the problems per input, on stdout.
+    Json,
+}
+
+impl ReportFormat {
+    pub fn for_str(format: &str) -> Result<Self, ()> {
+        match format {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+// Walks a serialized document to find what the conversion into a Value hides.
+struct Lint<'a> {
+    path: Vec<String>,
+    problems: &'a RefCell<Vec<String>>,
+}
+
+impl<'a> Lint<'a> {
+    fn report(&self, message: &str) {
+        self.problems.borrow_mut().push(format!("{} at '{}'", message, patch::to_pointer(&self.path)));
+    }
+
+    fn child(&self, token: String) -> Self {
+        let mut path = self.path.clone();
+        path.push(token);
+        Lint {
+            path,
+            problems: self.problems,
+        }
+    }
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for Lint<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for Lint<'a> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a document")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    // The integers overflowing i64 are reported when reading the document.
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut idx = 0;
+        while seq.next_element_seed(self.child(idx.to_string()))?.is_some() {
+            idx += 1;
+        } // while
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(key) = map.next_key_seed(KeySeed)? {
+            let (name, is_string) =
+                    match key {
+                        Key::Name(n) => (n, true),
+                        Key::Other(n) => (n, false),
+                    };
+            let child = self.child(name.clone());
+            if !is_string {
+                child.report(&format!("non-string key {}", name));
+            }
+            if !seen.insert(name) {
+                child.report("duplicate key");
+            }
+            map.next_value_seed(child)?;
+        } // while
+        Ok(())
+    }
+}
+
+// An Object key, either a string or the description of another value.
+enum Key {
+    Name(String),
+    Other(String),
+}
+
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Key;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Key, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed {
+    type Value = Key;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a key")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Key, E> {
+        Ok(Key::Other(v.to_string()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Key, E> {
+        Ok(Key::Other(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Key, E> {
+        Ok(Key::Other(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Key, E> {
+        Ok(Key::Other(v.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Key, E> {
+        Ok(Key::Name(v.to_owned()))
+    }
+
+    fn visit_unit<E>(self) -> Result<Key, E> {
+        Ok(Key::Other("null".to_owned()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Key, A::Error> {
+        while let Some(IgnoredAny) = seq.next_element()? {
+        } // while
+        Ok(Key::Other("[...]".to_owned()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Key, A::Error> {
+        while let Some((IgnoredAny, IgnoredAny)) = map.next_entry()? {
+        } // while
+        Ok(Key::Other("{...}".to_owned()))
+    }
+}
+
+// Collects the duplicate keys of the Object tuples of a given Lua document.
+fn lua_duplicate_keys(content: &str) -> Vec<String> {
+    let mut lua_content = String::new();
+    lua_content.push_str("ctx:set_output(");
+    lua_content.push_str(content);
+    lua_content.push_str(")\n");
+    let lua = rlua::Lua::new();
+    lua.context(|lua_ctx| {
+        let res = lua_ctx.load(command::LUA_PRELUDE).exec()
+            .and_then(|_| lua_ctx.load(LUA_DUPLICATE_KEYS).exec())
+            .and_then(|_| lua_ctx.load(&lua_content).exec())
+            .and_then(|_| lua_ctx.globals().get::<_, Vec<String>>("_duplicate_keys"));
+        // A wrong document is reported when reading it.
+        res.unwrap_or_default()
+    })
+}
+
+// Collects the problems of the raw text of a document.
+fn check_text(format: &FileFormat, content: &str, problems: &mut Vec<String>) {
+    if content.starts_with(BOM) {
+        problems.push("byte order mark".to_owned());
+    }
+    // The tabs are whitespaces elsewhere, but not a YAML indentation.
+    if *format != FileFormat::Yaml {
+        return;
+    }
+    for (idx, line) in content.lines().enumerate() {
+        let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+        if line[..indent].contains('\t') {
+            problems.push(format!("tab in indentation at line {}", idx + 1));
+        }
+    } // for
+}
+
+// Collects the problems of a given Value, located at a given path.
+fn check_value(value: &Value, path: &mut Vec<String>, problems: &mut Vec<String>) {
+    match value {
+        Value::Float(f) if f.is_nan() => problems.push(format!("NaN at '{}'", patch::to_pointer(path))),
+        Value::Float(f) if f.is_infinite() => problems.push(format!("infinity at '{}'", patch::to_pointer(path))),
+        Value::Array(a) => {
+            for (idx, v) in a.iter().enumerate() {
+                path.push(idx.to_string());
+                check_value(v, path, problems);
+                path.pop();
+            } // for
+        },
+        Value::Object(o) => {
+            for (k, v) in o.iter() {
+                path.push(k.clone());
+                check_value(v, path, problems);
+                path.pop();
+            } // for
+        },
+        _ => {},
+    }
+}
+
+// Returns the problems of a given document: whether it can be read by a given function and, in strict mode,
+// what reading it would silently lose or accept.
+pub fn check_document<F>(format: &FileFormat, content: &str, strict: bool, read: &F) -> Vec<String>
+        where F: Fn(&str) -> Result<Value, String> {
+    let mut problems = Vec::new();
+    if strict && *format != FileFormat::Plain {
+        check_text(format, content, &mut problems);
+
+        let lints = RefCell::new(Vec::new());
+        let lint = Lint {
+            path: Vec::new(),
+            problems: &lints,
+        };
+        // The syntax errors are reported when reading the document.
+        match format {
+            FileFormat::Json => {
+                let mut de = serde_json::Deserializer::from_str(content);
+                let _ = lint.deserialize(&mut de);
+            },
+            FileFormat::Toml => {
+                let mut de = toml::Deserializer::new(content);
+                let _ = lint.deserialize(&mut de);
+            },
+            FileFormat::Yaml => {
+                let _ = lint.deserialize(serde_yaml::Deserializer::from_str(content));
+            },
+            FileFormat::Lua => {
+                for key in lua_duplicate_keys(content).iter() {
+                    lints.borrow_mut().push(format!("duplicate key '{}'", key));
+                } // for
+            },
+            // The other formats have no duplicate keys to lint.
+            _ => {},
+        }
+        problems.append(&mut lints.borrow_mut());
+    }
+
+    match read(content) {
+        Ok(v) => {
+            if strict {
+                check_value(&v, &mut Vec::new(), &mut problems);
+            }
+        },
+        Err(e) => problems.push(e),
+    }
+
+    problems
+}
+
+// Returns the problems of a given YAML stream, whose documents are read by a given function,
+// prefixed by the index of their document.
+pub fn check_stream<F>(content: &str, strict: bool, read: &F) -> Vec<String>
+        where F: Fn(&str) -> Result<Value, String> {
+    let docs =
+            match yaml::read_stream(content) {
+                Ok(d) => d,
+                Err(_) => return vec!["wrong stream".to_owned()],
+            };
+
+    let mut problems = Vec::new();
+    for (doc_idx, doc) in docs.iter().enumerate() {
+        for problem in check_document(&FileFormat::Yaml, doc, strict, read) {
+            problems.push(format!("document {}: {}", doc_idx, problem));
+        } // for
+    } // for
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(format: &FileFormat, content: &str, strict: bool) -> Vec<String> {
+        check_document(format, content, strict, &|c| crate::read_document(format, c))
+    }
+
+    mod check_document {
+        use super::*;
+
+        #[test]
+        fn it_accepts_sound_documents() {
+            assert!(check(&FileFormat::Json, r#"{"a":[1,{"b":2}]}"#, true).is_empty());
+            assert!(check(&FileFormat::Yaml, "a:\n  - 1\n  - b: 2\n", true).is_empty());
+            assert!(check(&FileFormat::Lua, "Object:new({{'a', 1}, {'b', 2}})", true).is_empty());
+        }
+
+        #[test]
+        fn it_finds_duplicate_keys() {
+            let res = check(&FileFormat::Json, r#"{"a":{"b":1,"b":2}}"#, true);
+            assert_eq!(res, vec!["duplicate key at '/a/b'"]);
+            let res = check(&FileFormat::Yaml, "a: 1\nb:\n  - c: 1\n    c: 2\n", true);
+            assert_eq!(res, vec!["duplicate key at '/b/0/c'"]);
+            let res = check(&FileFormat::Lua, "Object:new({{'a', 1}, {'a', 2}})", true);
+            assert_eq!(res, vec!["duplicate key 'a'"]);
+            assert!(check(&FileFormat::Json, r#"{"a":1,"a":2}"#, false).is_empty());
+        }
+
+        #[test]
+        fn it_finds_lossy_values() {
+            let res = check(&FileFormat::Yaml, "1: a\nb: .nan\nc: -.inf\n", true);
+            assert_eq!(res, vec!["non-string key 1 at '/1'", "NaN at '/b'", "infinity at '/c'"]);
+            let res = check(&FileFormat::Json, r#"{"a":9223372036854775808}"#, true);
+            assert_eq!(res, vec!["integer 9223372036854775808 overflows at '/a'"]);
+            let res = check(&FileFormat::Json, r#"{"a":9223372036854775808}"#, false);
+            assert_eq!(res, vec!["integer 9223372036854775808 overflows at '/a'"]);
+        }
+
+        #[test]
+        fn it_finds_text_problems() {
+            let res = check(&FileFormat::Json, "\u{FEFF}{\n\t\"a\": 1\n}", true);
+            assert_eq!(res.len(), 2);
+            assert_eq!(res[0], "byte order mark");
+            let res = check(&FileFormat::Yaml, "a:\n\t- 1\n", true);
+            assert_eq!(res[0], "tab in indentation at line 2");
+            assert!(check(&FileFormat::Json, "{\n\t\"a\": 1\n}", true).is_empty());
+        }
+    }
+
+    mod check_stream {
+        use super::*;
+
+        #[test]
+        fn it_reports_documents_by_index() {
+            let read = |c: &str| crate::read_document(&FileFormat::Yaml, c);
+            assert!(check_stream("---\na: 1\n---\nb: 2\n", true, &read).is_empty());
+            let res = check_stream("---\na: 1\n---\nb: [\n", false, &read);
+            assert_eq!(res.len(), 1);
+            assert!(res[0].starts_with("document 1: "));
+        }
+    }
+}
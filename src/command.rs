@@ -350,3 +350,49 @@ end
 
 ctx = Context:new()
 "#;
+
+pub const LUA_STRICT: &str = r#"
+-- Raises an error when reading the value of a missing key.
+-- @param key [string]
+-- @return [any]
+function Object:get(key)
+    local val = self._values[key]
+    if val == nil then
+        error("undefined key '" .. tostring(key) .. "'", 2)
+    end
+
+    return val
+end
+
+-- Raises an error when reading an undeclared global variable.
+local _declared = {}
+setmetatable(_G, {
+    __newindex = function (t, name, value)
+        _declared[name] = true
+        rawset(t, name, value)
+    end,
+    __index = function (_, name)
+        if not _declared[name] then
+            error("undefined variable '" .. tostring(name) .. "'", 2)
+        end
+
+        return nil
+    end,
+})
+"#;
+
+// Returns the first line of the message of a given Lua error.
+pub fn lua_error_message(err: &rlua::Error) -> String {
+    let msg =
+            match err {
+                rlua::Error::SyntaxError { message, .. } => message.clone(),
+                rlua::Error::RuntimeError(m) => m.clone(),
+                rlua::Error::CallbackError { cause, .. } => return lua_error_message(cause),
+                e => format!("{}", e),
+            };
+
+    match msg.lines().next() {
+        Some(l) => l.to_owned(),
+        None => msg,
+    }
+}
@@ -0,0 +1,293 @@
+use indexmap::IndexMap;
+use crate::patch;
+use crate::value::{self, Value};
+
+// The output formats of a diff.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    // One line per difference.
+    #[default]
+    Listing,
+    // A JSON patch, as of RFC 6902.
+    Patch,
+    // A JSON merge patch, as of RFC 7396.
+    MergePatch,
+}
+
+impl DiffFormat {
+    pub fn for_str(format: &str) -> Result<Self, ()> {
+        match format {
+            "listing" => Ok(DiffFormat::Listing),
+            "patch" => Ok(DiffFormat::Patch),
+            "merge-patch" => Ok(DiffFormat::MergePatch),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiffOptions {
+    pub format: DiffFormat,
+    // Objects having the same entries in another order are equal.
+    pub ignore_key_order: bool,
+    // Arrays having the same elements in another order are equal.
+    pub ignore_array_order: bool,
+}
+
+// A difference between 2 Values, located by the tokens of its path.
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added(Vec<String>, Value),
+    Removed(Vec<String>, Value),
+    Changed(Vec<String>, Value, Value),
+}
+
+impl Change {
+    fn path(&self) -> &[String] {
+        match self {
+            Change::Added(p, _) => p,
+            Change::Removed(p, _) => p,
+            Change::Changed(p, _, _) => p,
+        }
+    }
+}
+
+// Tests whether 2 given Values are equal, where 1 and 1.0 are equal.
+pub fn equivalent(left: &Value, right: &Value, options: &DiffOptions) -> bool {
+    match (left, right) {
+        (Value::Integer(l), Value::Float(r)) => (*l as f64) == *r,
+        (Value::Float(l), Value::Integer(r)) => *l == (*r as f64),
+        (Value::Array(l), Value::Array(r)) => {
+            if l.len() != r.len() {
+                return false;
+            }
+            if !options.ignore_array_order {
+                return l.iter().zip(r).all(|(a, b)| equivalent(a, b, options));
+            }
+
+            // Matches every left element with a distinct right element.
+            let mut used = vec![false; r.len()];
+            for left_val in l.iter() {
+                match (0..r.len()).find(|idx| !used[*idx] && equivalent(left_val, &r[*idx], options)) {
+                    Some(idx) => used[idx] = true,
+                    None => return false,
+                }
+            } // for
+            true
+        },
+        (Value::Object(l), Value::Object(r)) => {
+            if l.len() != r.len() {
+                return false;
+            }
+            if !options.ignore_key_order && !l.keys().eq(r.keys()) {
+                return false;
+            }
+            l.iter().all(|(k, v)| match r.get(k) {
+                Some(rv) => equivalent(v, rv, options),
+                None => false,
+            })
+        },
+        _ => left == right,
+    }
+}
+
+// Collects the differences between 2 given Values located at a given path.
+fn collect_changes(left: &Value, right: &Value, options: &DiffOptions, path: &mut Vec<String>, changes: &mut Vec<Change>) {
+    if equivalent(left, right, options) {
+        return;
+    }
+
+    match (left, right) {
+        (Value::Array(l), Value::Array(r)) if !options.ignore_array_order => {
+            let common = l.len().min(r.len());
+            for idx in 0..common {
+                path.push(idx.to_string());
+                collect_changes(&l[idx], &r[idx], options, path, changes);
+                path.pop();
+            } // for
+            for (idx, right_val) in r.iter().enumerate().skip(common) {
+                let mut p = path.clone();
+                p.push(idx.to_string());
+                changes.push(Change::Added(p, right_val.clone()));
+            } // for
+            // The last elements are removed first, so that the indexes remain valid.
+            for (idx, left_val) in l.iter().enumerate().skip(common).rev() {
+                let mut p = path.clone();
+                p.push(idx.to_string());
+                changes.push(Change::Removed(p, left_val.clone()));
+            } // for
+        },
+        (Value::Object(l), Value::Object(r)) => {
+            let left_keys = l.keys().filter(|k| r.contains_key(*k));
+            let right_keys = r.keys().filter(|k| l.contains_key(*k));
+            if !options.ignore_key_order && !left_keys.eq(right_keys) {
+                // The common entries were reordered.
+                changes.push(Change::Changed(path.clone(), left.clone(), right.clone()));
+                return;
+            }
+
+            for (key, left_val) in l.iter() {
+                let mut p = path.clone();
+                p.push(key.clone());
+                match r.get(key) {
+                    Some(right_val) => collect_changes(left_val, right_val, options, &mut p, changes),
+                    None => changes.push(Change::Removed(p, left_val.clone())),
+                }
+            } // for
+            for (key, right_val) in r.iter() {
+                if !l.contains_key(key) {
+                    let mut p = path.clone();
+                    p.push(key.clone());
+                    changes.push(Change::Added(p, right_val.clone()));
+                }
+            } // for
+        },
+        _ => changes.push(Change::Changed(path.clone(), left.clone(), right.clone())),
+    }
+}
+
+// Returns the differences between 2 given Values.
+pub fn diff_values(left: &Value, right: &Value, options: &DiffOptions) -> Vec<Change> {
+    let mut changes = Vec::new();
+    collect_changes(left, right, options, &mut Vec::new(), &mut changes);
+
+    changes
+}
+
+// Returns a given Value as compact JSON.
+fn to_json(value: &Value) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+// Creates a listing of given differences, one line per difference.
+pub fn to_listing(changes: &[Change]) -> String {
+    let lines: Vec<String> = changes.iter()
+        .map(|c| match c {
+            Change::Added(p, v) => format!("+ {}: {}", patch::to_pointer(p), to_json(v)),
+            Change::Removed(p, v) => format!("- {}: {}", patch::to_pointer(p), to_json(v)),
+            Change::Changed(p, l, r) => format!("~ {}: {} -> {}", patch::to_pointer(p), to_json(l), to_json(r)),
+        })
+        .collect();
+
+    lines.join("\n")
+}
+
+// Creates a JSON patch from given differences.
+pub fn to_patch(changes: &[Change]) -> Value {
+    let ops = changes.iter()
+        .map(|c| {
+            let mut op = IndexMap::new();
+            let (name, value) =
+                    match c {
+                        Change::Added(_, v) => ("add", Some(v)),
+                        Change::Removed(_, _) => ("remove", None),
+                        Change::Changed(_, _, r) => ("replace", Some(r)),
+                    };
+            op.insert("op".to_owned(), Value::String(name.to_owned()));
+            op.insert("path".to_owned(), Value::String(patch::to_pointer(c.path())));
+            if let Some(v) = value {
+                op.insert("value".to_owned(), v.clone());
+            }
+            Value::Object(op)
+        })
+        .collect();
+
+    Value::Array(ops)
+}
+
+// Creates the output of the differences between 2 given Values.
+pub fn format_diff(left: &Value, right: &Value, changes: &[Change], options: &DiffOptions) -> Result<Value, String> {
+    match options.format {
+        DiffFormat::Listing => Ok(Value::String(to_listing(changes))),
+        DiffFormat::Patch => Ok(to_patch(changes)),
+        // The differences ignored by the options are not part of the merge patch.
+        DiffFormat::MergePatch if changes.is_empty() => Ok(Value::Object(IndexMap::new())),
+        DiffFormat::MergePatch => value::merge_patch_diff_by(left, right, |l, r| equivalent(l, r, options)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod diff_values {
+        use super::*;
+
+        #[test]
+        fn it_lists_changes() {
+            let left = new_value(r#"{"a":1,"b":{"c":true},"d":[1,2,3]}"#);
+            let right = new_value(r#"{"a":1.0,"b":{"c":false},"d":[1,2],"e":"x"}"#);
+
+            let changes = diff_values(&left, &right, &DiffOptions::default());
+            assert_eq!(to_listing(&changes), "~ /b/c: true -> false\n- /d/2: 3\n+ /e: \"x\"");
+        }
+
+        #[test]
+        fn it_checks_key_order() {
+            let left = new_value(r#"{"a":1,"b":2}"#);
+            let right = new_value(r#"{"b":2,"a":1}"#);
+
+            let mut options = DiffOptions::default();
+            assert_eq!(diff_values(&left, &right, &options).len(), 1);
+            options.ignore_key_order = true;
+            assert!(diff_values(&left, &right, &options).is_empty());
+        }
+
+        #[test]
+        fn it_checks_array_order() {
+            let left = new_value(r#"[[1,2],3,3]"#);
+            let right = new_value(r#"[3,[2,1],3]"#);
+
+            let mut options = DiffOptions::default();
+            assert_eq!(diff_values(&left, &right, &options).len(), 2);
+            options.ignore_array_order = true;
+            assert!(diff_values(&left, &right, &options).is_empty());
+        }
+    }
+
+    mod to_patch {
+        use super::*;
+
+        #[test]
+        fn it_creates_applicable_patches() {
+            let left = new_value(r#"{"a":{"b":[1,2,3,4]},"c":1}"#);
+            let right = new_value(r#"{"a":{"b":[0,2]},"d":2}"#);
+
+            let changes = diff_values(&left, &right, &DiffOptions::default());
+            let res = patch::apply_patch(&left, &to_patch(&changes));
+            assert_eq!(res, Ok(right));
+        }
+    }
+
+    mod format_diff {
+        use super::*;
+
+        #[test]
+        fn it_creates_applicable_merge_patches() {
+            let left = new_value(r#"{"a":{"b":[1,2],"c":1},"d":1}"#);
+            let right = new_value(r#"{"a":{"b":[1,3],"c":1},"e":2}"#);
+            let options = DiffOptions { format: DiffFormat::MergePatch, ..DiffOptions::default() };
+
+            let changes = diff_values(&left, &right, &options);
+            let res = format_diff(&left, &right, &changes, &options).unwrap();
+            assert_eq!(res, new_value(r#"{"a":{"b":[1,3]},"d":null,"e":2}"#));
+            assert_eq!(value::merge_patch(&left, &res), right);
+        }
+
+        #[test]
+        fn it_leaves_out_ignored_differences() {
+            let left = new_value(r#"{"a":[1,2],"b":{"c":1,"d":2},"e":1}"#);
+            let right = new_value(r#"{"a":[2,1],"b":{"d":2,"c":1.0},"e":2}"#);
+            let options = DiffOptions {
+                format: DiffFormat::MergePatch,
+                ignore_key_order: true,
+                ignore_array_order: true,
+            };
+
+            let changes = diff_values(&left, &right, &options);
+            let res = format_diff(&left, &right, &changes, &options).unwrap();
+            assert_eq!(res, new_value(r#"{"e":2}"#));
+        }
+    }
+}
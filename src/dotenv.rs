@@ -0,0 +1,236 @@
+use indexmap::IndexMap;
+use crate::value::{self, Value};
+
+const EXPORT_PREFIX: &str = "export ";
+
+// Tests whether a given name is a variable name.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)),
+        _ => false,
+    }
+}
+
+// Reads a quoted value, after its opening quote, and returns it with the rest of the content.
+// Only the double-quoted values have escapes.
+fn read_quoted(content: &str, quote: char) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = content.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        if c == quote {
+            return Ok((value, &content[idx + 1..]));
+        }
+        if c == '\\' && quote == '"' {
+            match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, e @ ('\\' | '"' | '$' | '`'))) => value.push(e),
+                // A line continuation.
+                Some((_, '\n')) => {},
+                Some((_, e)) => {
+                    value.push('\\');
+                    value.push(e);
+                },
+                None => break,
+            }
+        } else {
+            value.push(c);
+        }
+    } // while
+
+    Err(format!("unterminated {} quote", if quote == '"' { "double" } else { "single" }))
+}
+
+// Reads an unquoted value up to the end of its line, and returns it with the rest of the content.
+// A trailing backslash continues the value on the next line, and ' #' starts a comment.
+fn read_unquoted(content: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut rest = content;
+    loop {
+        let (line, next) =
+                match rest.find('\n') {
+                    Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+                    None => (rest, ""),
+                };
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line =
+                match line.find(" #").or_else(|| line.find("\t#")) {
+                    Some(idx) => &line[..idx],
+                    None => line,
+                };
+        rest = next;
+        match line.strip_suffix('\\') {
+            Some(l) if !next.is_empty() => value.push_str(l),
+            _ => {
+                value.push_str(line);
+                break;
+            },
+        }
+    } // loop
+
+    (value.trim_end().to_owned(), rest)
+}
+
+// Reads a dotenv document into a flat Object of strings.
+pub fn from_str(content: &str) -> Result<Value, String> {
+    let mut res = IndexMap::new();
+    let mut rest = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+    let mut line_nb = 1;
+    while !rest.is_empty() {
+        let (line, next) =
+                match rest.find('\n') {
+                    Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+                    None => (rest, ""),
+                };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            rest = next;
+            line_nb += 1;
+            continue;
+        }
+
+        let eq =
+                match line.find('=') {
+                    Some(idx) => idx,
+                    None => return Err(format!("line {}: missing '='", line_nb)),
+                };
+        let name = line[..eq].trim();
+        let name = name.strip_prefix(EXPORT_PREFIX).map(str::trim_start).unwrap_or(name);
+        if !is_name(name) {
+            return Err(format!("line {}: wrong variable name '{}'", line_nb, name));
+        }
+
+        // The value may span multiple lines, so it is read from the rest of the content.
+        let value = rest[eq + 1..].trim_start_matches([' ', '\t']);
+        let (value, after) =
+                match value.chars().next() {
+                    Some(q @ ('"' | '\'')) => {
+                        let (v, after) =
+                                match read_quoted(&value[1..], q) {
+                                    Ok(r) => r,
+                                    Err(e) => return Err(format!("line {}: {}", line_nb, e)),
+                                };
+                        let (tail, after) =
+                                match after.find('\n') {
+                                    Some(idx) => (&after[..idx], &after[idx + 1..]),
+                                    None => (after, ""),
+                                };
+                        let tail = tail.trim();
+                        if !tail.is_empty() && !tail.starts_with('#') {
+                            return Err(format!("line {}: unexpected '{}' after the quoted value", line_nb, tail));
+                        }
+                        (v, after)
+                    },
+                    _ => read_unquoted(value),
+                };
+        line_nb += rest[..rest.len() - after.len()].matches('\n').count();
+        res.insert(name.to_owned(), Value::String(value));
+        rest = after;
+    } // while
+
+    Ok(Value::Object(res))
+}
+
+// Returns a given value, quoted when needed.
+fn quote(value: &str) -> String {
+    if value.chars().all(|c| c.is_ascii_alphanumeric() || "_./:@,+-".contains(c)) {
+        value.to_owned()
+    } else if !value.contains(['\'', '\n', '\r']) {
+        format!("'{}'", value)
+    } else {
+        let mut sb = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '\n' => sb.push_str("\\n"),
+                '\r' => sb.push_str("\\r"),
+                '\t' => sb.push_str("\\t"),
+                '\\' | '"' | '$' | '`' => {
+                    sb.push('\\');
+                    sb.push(c);
+                },
+                _ => sb.push(c),
+            }
+        } // for
+        sb.push('"');
+        sb
+    }
+}
+
+// Writes a flat Object of scalars as a dotenv document.
+pub fn to_string(value: &Value) -> Result<String, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("env requires an object".to_owned()),
+            };
+
+    let mut sb = String::new();
+    for (k, v) in o.iter() {
+        if !is_name(k) {
+            return Err(format!("wrong variable name '{}'", k));
+        }
+        let v =
+                match value::scalar_to_string(v) {
+                    Some(s) => s,
+                    None => return Err(format!("nested value at '{}'", k)),
+                };
+        sb.push_str(k);
+        sb.push('=');
+        sb.push_str(&quote(&v));
+        sb.push('\n');
+    } // for
+
+    Ok(sb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn it_reads_entries() {
+            let content = concat!(
+                "# comment\n",
+                "export HOST = db.local # host\n",
+                "PORT=5432\n",
+                "\n",
+                "EMPTY=\n",
+                "NAME='a #b' \n",
+                "MSG=\"a \\\"b\\\"\n\\tc\\$\" # msg\n",
+                "LONG=a\\\n",
+                "b\n",
+                "URL=http://x/#y\n",
+            );
+
+            let res = from_str(content);
+            assert_eq!(res, Ok(new_value(r#"{"HOST":"db.local","PORT":"5432","EMPTY":"","NAME":"a #b","MSG":"a \"b\"\n\tc$","LONG":"ab","URL":"http://x/#y"}"#)));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_entries() {
+            assert_eq!(from_str("A=1\nB\n"), Err("line 2: missing '='".to_owned()));
+            assert_eq!(from_str("A=1\n1B=2\n"), Err("line 2: wrong variable name '1B'".to_owned()));
+            assert_eq!(from_str("A=\"1\n\nB=2\n"), Err("line 1: unterminated double quote".to_owned()));
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_quotes_values() {
+            let doc = new_value(r#"{"A":"db.local:5432","B":"a b","C":"it's\n$x","D":true,"E":null}"#);
+
+            let res = to_string(&doc);
+            assert_eq!(res, Ok("A=db.local:5432\nB='a b'\nC=\"it's\\n\\$x\"\nD=true\nE=\n".to_owned()));
+            assert_eq!(from_str(&res.unwrap()), Ok(new_value(r#"{"A":"db.local:5432","B":"a b","C":"it's\n$x","D":"true","E":""}"#)));
+            assert_eq!(to_string(&new_value(r#"{"A":{"B":1}}"#)), Err("nested value at 'A'".to_owned()));
+        }
+    }
+}
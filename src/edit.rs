@@ -0,0 +1,189 @@
+use indexmap::IndexMap;
+use crate::patch;
+use crate::value::Value;
+
+// An edit of a document, at paths given as tokens.
+#[derive(Debug, PartialEq)]
+pub enum Edit {
+    Set(Vec<String>, Value),
+    Delete(Vec<String>),
+    Rename(Vec<String>, Vec<String>),
+}
+
+impl Edit {
+    // Returns the command name of the edit.
+    pub fn name(&self) -> &str {
+        match self {
+            Edit::Set(_, _) => "set",
+            Edit::Delete(_) => "delete",
+            Edit::Rename(_, _) => "rename",
+        }
+    }
+}
+
+// Parses a given path, either a JSON pointer or a dotted path, where '\.' escapes a dot.
+pub fn parse_path(path: &str) -> Result<Vec<String>, String> {
+    if path.is_empty() || path.starts_with('/') {
+        return patch::parse_pointer(path);
+    }
+
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(n) => token.push(n),
+                None => token.push(c),
+            },
+            '.' => tokens.push(std::mem::take(&mut token)),
+            _ => token.push(c),
+        }
+    } // while
+    tokens.push(token);
+    if tokens.iter().any(|t| t.is_empty()) {
+        return Err(format!("wrong path '{}'", path));
+    }
+
+    Ok(tokens)
+}
+
+// Sets a Value at a given path, creating the missing intermediate Objects.
+// An existing Object entry keeps its position, a new one is appended.
+fn set(root: &mut Value, tokens: &[String], new_value: Value) -> Result<(), String> {
+    let mut current = root;
+    for (depth, token) in tokens.iter().enumerate() {
+        let is_last = depth + 1 == tokens.len();
+        if let Value::Nil = current {
+            *current = Value::Object(IndexMap::new());
+        }
+        current =
+                match current {
+                    Value::Object(o) => {
+                        if is_last {
+                            o.insert(token.clone(), new_value);
+                            return Ok(());
+                        }
+                        o.entry(token.clone()).or_insert(Value::Nil)
+                    },
+                    Value::Array(a) => {
+                        let idx = patch::array_index(token, a.len(), true)?;
+                        if idx == a.len() {
+                            a.push(Value::Nil);
+                        }
+                        if is_last {
+                            a[idx] = new_value;
+                            return Ok(());
+                        }
+                        &mut a[idx]
+                    },
+                    _ => return Err(format!("'{}' is not a container", patch::to_pointer(&tokens[..depth]))),
+                };
+    } // for
+
+    // The path is the root.
+    *current = new_value;
+    Ok(())
+}
+
+// Renames an Object entry in place, keeping its position.
+fn rename_entry(o: &mut IndexMap<String, Value>, old: &str, new: &str) {
+    let entries = std::mem::take(o);
+    *o = entries.into_iter()
+        .map(|(k, v)| if k == old { (new.to_owned(), v) } else { (k, v) })
+        .collect();
+}
+
+// Creates a new Value by applying a given edit to a given document.
+pub fn apply_edit(doc: &Value, edit: &Edit) -> Result<Value, String> {
+    let mut res = doc.clone();
+    match edit {
+        Edit::Set(path, v) => set(&mut res, path, v.clone())?,
+        Edit::Delete(path) => {
+            // Deleting a missing Value does nothing.
+            if patch::get(&res, path).is_some() {
+                patch::remove(&mut res, path)?;
+            }
+        },
+        Edit::Rename(old, new) => {
+            if patch::get(&res, old).is_none() {
+                return Err(format!("'{}' does not exist", patch::to_pointer(old)));
+            }
+            if patch::get(&res, new).is_some() {
+                return Err(format!("'{}' already exists", patch::to_pointer(new)));
+            }
+
+            let same_parent = !old.is_empty() && !new.is_empty() && old[..old.len() - 1] == new[..new.len() - 1];
+            match patch::get_mut(&mut res, &old[..old.len().saturating_sub(1)]) {
+                Some(Value::Object(o)) if same_parent => rename_entry(o, old.last().unwrap(), new.last().unwrap()),
+                _ => {
+                    let v = patch::remove(&mut res, old)?;
+                    set(&mut res, new, v)?;
+                },
+            }
+        },
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{self, new_value};
+
+    fn tokens(path: &str) -> Vec<String> {
+        parse_path(path).unwrap()
+    }
+
+    mod parse_path {
+        use super::*;
+
+        #[test]
+        fn it_parses_paths() {
+            assert_eq!(tokens("a.b.0"), vec!["a", "b", "0"]);
+            assert_eq!(tokens(r"metadata.annotations.app\.io/name"), vec!["metadata", "annotations", "app.io/name"]);
+            assert_eq!(tokens("/a~1b/c"), vec!["a/b", "c"]);
+            assert_eq!(tokens(""), Vec::<String>::new());
+            assert!(parse_path("a..b").is_err());
+        }
+    }
+
+    mod apply_edit {
+        use super::*;
+
+        #[test]
+        fn it_sets_values() {
+            let doc = new_value(r#"{"image":{"name":"app","tag":"1.0"},"ports":[80]}"#);
+
+            let res = apply_edit(&doc, &Edit::Set(tokens("image.tag"), Value::String("1.2".to_owned())));
+            assert_eq!(res, Ok(new_value(r#"{"image":{"name":"app","tag":"1.2"},"ports":[80]}"#)));
+            let res = apply_edit(&doc, &Edit::Set(tokens("/resources/limits/cpu"), Value::Integer(1)));
+            assert_eq!(res, Ok(new_value(r#"{"image":{"name":"app","tag":"1.0"},"ports":[80],"resources":{"limits":{"cpu":1}}}"#)));
+            let res = apply_edit(&doc, &Edit::Set(tokens("ports.-"), Value::Integer(443)));
+            assert_eq!(res, Ok(new_value(r#"{"image":{"name":"app","tag":"1.0"},"ports":[80,443]}"#)));
+            let res = apply_edit(&doc, &Edit::Set(tokens("image.name.first"), Value::Nil));
+            assert_eq!(res, Err("'/image/name' is not a container".to_owned()));
+        }
+
+        #[test]
+        fn it_deletes_values() {
+            let doc = new_value(r#"{"a":1,"secret":"x","b":2}"#);
+
+            assert_eq!(apply_edit(&doc, &Edit::Delete(tokens("secret"))), Ok(new_value(r#"{"a":1,"b":2}"#)));
+            assert_eq!(apply_edit(&doc, &Edit::Delete(tokens("missing.key"))), Ok(doc));
+        }
+
+        #[test]
+        fn it_renames_values() {
+            let doc = new_value(r#"{"a":1,"b":{"c":2},"d":3}"#);
+
+            let res = apply_edit(&doc, &Edit::Rename(tokens("b"), tokens("e")));
+            assert_eq!(res.map(|v| value::to_lua_string(&v)), Ok(value::to_lua_string(&new_value(r#"{"a":1,"e":{"c":2},"d":3}"#))));
+            let res = apply_edit(&doc, &Edit::Rename(tokens("b.c"), tokens("f.g")));
+            assert_eq!(res, Ok(new_value(r#"{"a":1,"b":{},"d":3,"f":{"g":2}}"#)));
+            let res = apply_edit(&doc, &Edit::Rename(tokens("a"), tokens("d")));
+            assert_eq!(res, Err("'/d' already exists".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use indexmap::IndexMap;
+use crate::value::Value;
+
+pub const DEFAULT_SEPARATOR: &str = ".";
+
+// The notations of the array indices within flat keys.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayStyle {
+    // As any other key: 'a.0.b'.
+    #[default]
+    Index,
+    // Within brackets: 'a[0].b'.
+    Brackets,
+}
+
+impl ArrayStyle {
+    pub fn for_str(style: &str) -> Result<Self, ()> {
+        match style {
+            "index" => Ok(ArrayStyle::Index),
+            "brackets" => Ok(ArrayStyle::Brackets),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenOptions {
+    // The separator of the keys within a flat key.
+    pub separator: String,
+    pub array_style: ArrayStyle,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            separator: DEFAULT_SEPARATOR.to_owned(),
+            array_style: ArrayStyle::default(),
+        }
+    }
+}
+
+// Inserts the flat entries of a given Value located at a given flat key.
+// The empty Objects and Arrays are kept as values.
+fn flatten_value(value: &Value, key: &str, options: &FlattenOptions, res: &mut IndexMap<String, Value>) {
+    match value {
+        Value::Object(o) if !o.is_empty() => {
+            for (k, v) in o.iter() {
+                let child =
+                        if key.is_empty() {
+                            k.clone()
+                        } else {
+                            format!("{}{}{}", key, options.separator, k)
+                        };
+                flatten_value(v, &child, options, res);
+            } // for
+        },
+        Value::Array(a) if !a.is_empty() => {
+            for (idx, v) in a.iter().enumerate() {
+                let child =
+                        match options.array_style {
+                            ArrayStyle::Index if key.is_empty() => idx.to_string(),
+                            ArrayStyle::Index => format!("{}{}{}", key, options.separator, idx),
+                            ArrayStyle::Brackets => format!("{}[{}]", key, idx),
+                        };
+                flatten_value(v, &child, options, res);
+            } // for
+        },
+        _ => {
+            res.insert(key.to_owned(), value.clone());
+        },
+    }
+}
+
+// Creates a flat Object of the scalars of a given Object or Array, keyed by their paths.
+pub fn flatten(value: &Value, options: &FlattenOptions) -> Result<Value, String> {
+    match value {
+        Value::Object(_) | Value::Array(_) => {
+            let mut res = IndexMap::new();
+            flatten_value(value, "", options, &mut res);
+            Ok(Value::Object(res))
+        },
+        _ => Err("flatten requires an object or an array".to_owned()),
+    }
+}
+
+// A token of a flat key.
+enum Token {
+    Key(String),
+    Index(usize),
+}
+
+// Returns the tokens of a given flat key.
+fn parse_key(key: &str, options: &FlattenOptions) -> Vec<Token> {
+    let mut res = Vec::new();
+    for segment in key.split(options.separator.as_str()) {
+        if options.array_style == ArrayStyle::Brackets {
+            if let Some((name, mut indices)) = segment.find('[').map(|pos| segment.split_at(pos)) {
+                let mut tokens = Vec::new();
+                while let Some(idx) = indices.strip_prefix('[')
+                    .and_then(|s| s.split_once(']'))
+                    .and_then(|(i, rest)| i.parse::<usize>().ok().map(|i| (i, rest))) {
+                    tokens.push(Token::Index(idx.0));
+                    indices = idx.1;
+                } // while
+                if indices.is_empty() {
+                    if !name.is_empty() {
+                        res.push(Token::Key(name.to_owned()));
+                    }
+                    res.append(&mut tokens);
+                    continue;
+                }
+            }
+        }
+        res.push(Token::Key(segment.to_owned()));
+    } // for
+
+    res
+}
+
+// A Value being rebuilt.
+enum Node {
+    Leaf(Value),
+    // The children, and whether they are Array elements.
+    Branch(IndexMap<String, Node>, bool),
+}
+
+impl Node {
+    // Inserts a given Value at the given tokens of a given flat key.
+    fn insert(&mut self, tokens: &[Token], value: Value, key: &str) -> Result<(), String> {
+        let children =
+                match self {
+                    Node::Branch(c, _) => c,
+                    Node::Leaf(_) => return Err(format!("conflicting key '{}'", key)),
+                };
+        let name =
+                match &tokens[0] {
+                    Token::Key(k) => k.clone(),
+                    Token::Index(i) => i.to_string(),
+                };
+        if tokens.len() == 1 {
+            if children.contains_key(&name) {
+                return Err(format!("conflicting key '{}'", key));
+            }
+            children.insert(name, Node::Leaf(value));
+            return Ok(());
+        }
+
+        let is_array = matches!(tokens[1], Token::Index(_));
+        let child = children.entry(name).or_insert_with(|| Node::Branch(IndexMap::new(), is_array));
+        match child {
+            Node::Branch(_, a) if *a != is_array => Err(format!("conflicting key '{}'", key)),
+            _ => child.insert(&tokens[1..], value, key),
+        }
+    }
+
+    // Creates the Value of this Node.
+    // Without brackets, the Objects whose keys are the indices from 0 are Arrays.
+    // With brackets, the indices must be from 0 without gaps too, or they remain Object keys.
+    fn into_value(self, style: ArrayStyle) -> Value {
+        match self {
+            Node::Leaf(v) => v,
+            Node::Branch(children, is_array) => {
+                let mut indices: Vec<Option<usize>> = children.keys().map(|k| k.parse::<usize>().ok()).collect();
+                indices.sort_unstable();
+                let contiguous = !indices.is_empty() && indices.iter().enumerate().all(|(idx, i)| *i == Some(idx));
+                let is_array =
+                        match style {
+                            ArrayStyle::Brackets => is_array && contiguous,
+                            ArrayStyle::Index => contiguous,
+                        };
+                if is_array {
+                    let mut elements: Vec<(usize, Node)> = children.into_iter().map(|(k, v)| (k.parse().unwrap(), v)).collect();
+                    elements.sort_unstable_by_key(|(idx, _)| *idx);
+                    Value::Array(elements.into_iter().map(|(_, v)| v.into_value(style)).collect())
+                } else {
+                    Value::Object(children.into_iter().map(|(k, v)| (k, v.into_value(style))).collect())
+                }
+            },
+        }
+    }
+}
+
+// Creates a nested Value from a given flat Object, whose keys are the paths of the values.
+pub fn unflatten(value: &Value, options: &FlattenOptions) -> Result<Value, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("unflatten requires an object".to_owned()),
+            };
+
+    let mut root: Option<Node> = None;
+    for (k, v) in o.iter() {
+        let tokens = parse_key(k, options);
+        let node = root.get_or_insert_with(|| Node::Branch(IndexMap::new(), matches!(tokens[0], Token::Index(_))));
+        if let Node::Branch(_, is_array) = node {
+            if *is_array != matches!(tokens[0], Token::Index(_)) {
+                return Err(format!("conflicting key '{}'", k));
+            }
+        }
+        node.insert(&tokens, v.clone(), k)?;
+    } // for
+
+    Ok(match root {
+        Some(n) => n.into_value(options.array_style),
+        None => Value::Object(IndexMap::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    fn brackets() -> FlattenOptions {
+        FlattenOptions {
+            array_style: ArrayStyle::Brackets,
+            ..FlattenOptions::default()
+        }
+    }
+
+    mod flatten {
+        use super::*;
+
+        #[test]
+        fn it_flattens_with_indices() {
+            let doc = new_value(r#"{"b":{"c":[1,{"d":true}],"e":{}},"a":null}"#);
+
+            let res = flatten(&doc, &FlattenOptions::default());
+            assert_eq!(res, Ok(new_value(r#"{"b.c.0":1,"b.c.1.d":true,"b.e":{},"a":null}"#)));
+        }
+
+        #[test]
+        fn it_flattens_with_brackets() {
+            let doc = new_value(r#"[{"a":[[1,2]]}]"#);
+            let options = FlattenOptions {
+                separator: "__".to_owned(),
+                ..brackets()
+            };
+
+            let res = flatten(&doc, &options);
+            assert_eq!(res, Ok(new_value(r#"{"[0]__a[0][0]":1,"[0]__a[0][1]":2}"#)));
+            assert!(flatten(&Value::Integer(1), &options).is_err());
+        }
+    }
+
+    mod unflatten {
+        use super::*;
+
+        #[test]
+        fn it_reverses_flatten() {
+            let doc = new_value(r#"{"b":{"c":[1,{"d":true}],"e":{}},"a":[[]],"f":{"1":2}}"#);
+            for options in [FlattenOptions::default(), brackets()].iter() {
+                let res = flatten(&doc, options).and_then(|f| unflatten(&f, options));
+                assert_eq!(res, Ok(doc.clone()));
+            } // for
+        }
+
+        #[test]
+        fn it_turns_indexed_objects_into_arrays() {
+            let doc = new_value(r#"{"a":{"0":1}}"#);
+
+            let options = FlattenOptions::default();
+            let res = flatten(&doc, &options).and_then(|f| unflatten(&f, &options));
+            assert_eq!(res, Ok(new_value(r#"{"a":[1]}"#)));
+            let res = flatten(&doc, &brackets()).and_then(|f| unflatten(&f, &brackets()));
+            assert_eq!(res, Ok(doc));
+        }
+
+        #[test]
+        fn it_keeps_missing_indices_as_keys() {
+            let res = unflatten(&new_value(r#"{"a[2]":1,"a[0]":0}"#), &brackets());
+            assert_eq!(res, Ok(new_value(r#"{"a":{"2":1,"0":0}}"#)));
+            let res = unflatten(&new_value(r#"{"a[999999999999]":1}"#), &brackets());
+            assert_eq!(res, Ok(new_value(r#"{"a":{"999999999999":1}}"#)));
+            let res = unflatten(&new_value(r#"{"a[1]":1,"a[0]":0}"#), &brackets());
+            assert_eq!(res, Ok(new_value(r#"{"a":[0,1]}"#)));
+            let res = unflatten(&new_value(r#"{"a.2":1,"a.0":0}"#), &FlattenOptions::default());
+            assert_eq!(res, Ok(new_value(r#"{"a":{"2":1,"0":0}}"#)));
+        }
+
+        #[test]
+        fn it_fails_on_conflicts() {
+            let res = unflatten(&new_value(r#"{"a":1,"a.b":2}"#), &FlattenOptions::default());
+            assert_eq!(res, Err("conflicting key 'a.b'".to_owned()));
+            let res = unflatten(&new_value(r#"{"a.b":1,"a[0]":2}"#), &brackets());
+            assert_eq!(res, Err("conflicting key 'a[0]'".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use indexmap::IndexMap;
+use crate::value::{self, Value};
+
+const YAML_DELIMITER: &str = "---";
+const YAML_END_DELIMITER: &str = "...";
+const TOML_DELIMITER: &str = "+++";
+
+// The formats of the front matter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MetaFormat {
+    // Between '---' lines.
+    #[default]
+    Yaml,
+    // Between '+++' lines.
+    Toml,
+}
+
+impl MetaFormat {
+    pub fn for_str(format: &str) -> Result<Self, ()> {
+        match format {
+            "yaml" => Ok(MetaFormat::Yaml),
+            "toml" => Ok(MetaFormat::Toml),
+            _ => Err(()),
+        }
+    }
+}
+
+// Splits a given content into its first line, without its line break, and the rest.
+fn split_line(content: &str) -> (&str, &str) {
+    let (line, rest) =
+            match content.find('\n') {
+                Some(idx) => (&content[..idx], &content[idx + 1..]),
+                None => (content, ""),
+            };
+
+    (line.trim_end(), rest)
+}
+
+fn new_document(meta: Value, body: &str) -> Value {
+    let mut new_obj = IndexMap::new();
+    new_obj.insert("meta".to_owned(), meta);
+    new_obj.insert("body".to_owned(), Value::String(body.to_owned()));
+
+    Value::Object(new_obj)
+}
+
+// Splits a given content into the format of its front matter, the front matter and the body, if it has one.
+// Without a closing delimiter, the opening one is a part of the text, like a Markdown horizontal rule.
+fn split(content: &str) -> Option<(MetaFormat, &str, &str)> {
+    let text = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+    let (first, mut rest) = split_line(text);
+    let format =
+            match first {
+                YAML_DELIMITER => MetaFormat::Yaml,
+                TOML_DELIMITER => MetaFormat::Toml,
+                _ => return None,
+            };
+
+    let start = rest;
+    loop {
+        if rest.is_empty() {
+            return None;
+        }
+        let (line, next) = split_line(rest);
+        let is_end =
+                match format {
+                    MetaFormat::Yaml => line == YAML_DELIMITER || line == YAML_END_DELIMITER,
+                    MetaFormat::Toml => line == TOML_DELIMITER,
+                };
+        if is_end {
+            break;
+        }
+        rest = next;
+    } // loop
+    let header = &start[..start.len() - rest.len()];
+    let (_, body) = split_line(rest);
+
+    Some((format, header, body))
+}
+
+// Returns the format of the front matter of a given text document, if it has one.
+pub fn meta_format(content: &str) -> Option<MetaFormat> {
+    split(content).map(|(format, _, _)| format)
+}
+
+// Reads a text document with a front matter into {"meta": META, "body": BODY}.
+// The front matter is YAML between '---' lines, or TOML between '+++' lines, and META is null without one.
+// BODY is the text following the front matter, as is.
+pub fn from_str(content: &str) -> Result<Value, String> {
+    let (format, header, body) =
+            match split(content) {
+                Some(s) => s,
+                None => return Ok(new_document(Value::Nil, content)),
+            };
+
+    // A front matter without entries, even made of comments only, is an empty Object, so that it is written back.
+    let is_empty = header.lines().map(str::trim).all(|l| l.is_empty() || l.starts_with('#'));
+    let meta =
+            if is_empty {
+                Value::Object(IndexMap::new())
+            } else {
+                match format {
+                    MetaFormat::Yaml => value::from_yaml_str(header),
+                    MetaFormat::Toml => value::from_toml_str(header),
+                }.map_err(|e| format!("front matter: {}", e))?
+            };
+
+    Ok(new_document(meta, body))
+}
+
+// Writes {"meta": META, "body": BODY} as a text document, whose front matter is META in a given format.
+// A null or missing META writes BODY only.
+pub fn to_string(value: &Value, format: MetaFormat) -> Result<String, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("frontmatter requires an object".to_owned()),
+            };
+    let body =
+            match o.get("body") {
+                Some(Value::String(s)) => s.as_str(),
+                None | Some(Value::Nil) => "",
+                Some(_) => return Err("wrong body".to_owned()),
+            };
+
+    let mut sb = String::new();
+    match o.get("meta") {
+        None | Some(Value::Nil) => {},
+        Some(meta) => {
+            match format {
+                MetaFormat::Yaml => {
+                    let header = serde_yaml::to_string(meta).map_err(|e| e.to_string())?;
+                    sb.push_str(YAML_DELIMITER);
+                    sb.push('\n');
+                    // The serialized YAML may start with its own document marker.
+                    let header = header.strip_prefix("---\n").unwrap_or(&header);
+                    if !matches!(meta, Value::Object(m) if m.is_empty()) {
+                        sb.push_str(header);
+                    }
+                    sb.push_str(YAML_DELIMITER);
+                },
+                MetaFormat::Toml => {
+                    if !matches!(meta, Value::Object(_)) {
+                        return Err("wrong meta".to_owned());
+                    }
+                    let header = toml::to_string(&value::fix_toml(meta)).map_err(|e| e.to_string())?;
+                    sb.push_str(TOML_DELIMITER);
+                    sb.push('\n');
+                    sb.push_str(&header);
+                    sb.push_str(TOML_DELIMITER);
+                },
+            }
+            sb.push('\n');
+        },
+    }
+    sb.push_str(body);
+
+    Ok(sb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn it_reads_front_matters() {
+            let res = from_str("---\ntitle: Hello\ntags: [a, b]\n---\n# Hello\n\n---\n");
+            assert_eq!(res, Ok(new_value(r##"{"meta":{"title":"Hello","tags":["a","b"]},"body":"# Hello\n\n---\n"}"##)));
+            let res = from_str("+++\r\ntitle = \"Hello\"\r\n+++\r\nText");
+            assert_eq!(res, Ok(new_value(r#"{"meta":{"title":"Hello"},"body":"Text"}"#)));
+            let res = from_str("---\n---\n");
+            assert_eq!(res, Ok(new_value(r#"{"meta":{},"body":""}"#)));
+            let res = from_str("---\n# A comment\n---\nText");
+            assert_eq!(res, Ok(new_value(r#"{"meta":{},"body":"Text"}"#)));
+        }
+
+        #[test]
+        fn it_reads_bodies_only() {
+            let res = from_str("# Hello\n---\n");
+            assert_eq!(res, Ok(new_value(r##"{"meta":null,"body":"# Hello\n---\n"}"##)));
+            // A horizontal rule, not an unterminated front matter.
+            let res = from_str("---\n\nText\n");
+            assert_eq!(res, Ok(new_value(r#"{"meta":null,"body":"---\n\nText\n"}"#)));
+        }
+    }
+
+    mod meta_format {
+        use super::*;
+
+        #[test]
+        fn it_finds_the_delimiter() {
+            assert_eq!(meta_format("---\ntitle: Hello\n---\nText"), Some(MetaFormat::Yaml));
+            assert_eq!(meta_format("+++\ntitle = \"Hello\"\n+++\nText"), Some(MetaFormat::Toml));
+            assert_eq!(meta_format("+++\nText"), None);
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_writes_front_matters() {
+            let doc = new_value(r##"{"meta":{"title":"Hello","tags":["a"]},"body":"# Hello\n"}"##);
+
+            let res = to_string(&doc, MetaFormat::Yaml);
+            assert_eq!(res, Ok("---\ntitle: Hello\ntags:\n  - a\n---\n# Hello\n".to_owned()));
+            let res = to_string(&doc, MetaFormat::Toml);
+            assert_eq!(res, Ok("+++\ntitle = \"Hello\"\ntags = [\"a\"]\n+++\n# Hello\n".to_owned()));
+        }
+
+        #[test]
+        fn it_round_trips() {
+            for content in ["---\ntitle: Hello\n---\nText", "+++\ntitle = \"Hello\"\n+++\nText", "---\n---\nText"].iter() {
+                let doc = from_str(content).unwrap();
+                let res = to_string(&doc, meta_format(content).unwrap());
+                assert_eq!(res.as_deref(), Ok(*content));
+            } // for
+            let doc = from_str("---\n# A comment\n---\nText").unwrap();
+            assert_eq!(to_string(&doc, MetaFormat::Yaml), Ok("---\n---\nText".to_owned()));
+        }
+
+        #[test]
+        fn it_writes_bodies_only() {
+            let res = to_string(&new_value(r#"{"meta":null,"body":"Text"}"#), MetaFormat::Yaml);
+            assert_eq!(res, Ok("Text".to_owned()));
+            let res = to_string(&new_value(r#"{"meta":1,"body":"Text"}"#), MetaFormat::Toml);
+            assert_eq!(res, Err("wrong meta".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use indexmap::IndexMap;
+use ::hcl::{Attribute, Block, Body, Expression, Identifier, Number, ObjectKey};
+use crate::value::Value;
+
+// Converts a given HCL value into an internal Value.
+fn from_hcl_value(value: &::hcl::Value) -> Value {
+    match value {
+        ::hcl::Value::Null => Value::Nil,
+        ::hcl::Value::Bool(b) => Value::Boolean(*b),
+        ::hcl::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        ::hcl::Value::String(s) => Value::String(s.clone()),
+        ::hcl::Value::Array(a) => Value::Array(a.iter().map(from_hcl_value).collect()),
+        ::hcl::Value::Object(o) => {
+            let new_obj: IndexMap<String, Value> = o.iter()
+                .map(|(k, v)| (k.clone(), from_hcl_value(v)))
+                .collect();
+
+            Value::Object(new_obj)
+        },
+    }
+}
+
+// Reads an HCL document, whose attributes are entries and blocks objects:
+// - 'NAME { ... }' is an entry NAME of the block body,
+// - 'NAME "L1" "L2" { ... }' is an entry NAME whose labels are nested objects: {"L1": {"L2": ...}},
+// - the repeated 'NAME { ... }' blocks are an array of their bodies.
+// The expressions which can't be evaluated, like 'var.region', are kept as '${var.region}' strings.
+pub fn from_str(content: &str) -> Result<Value, String> {
+    match ::hcl::from_str::<::hcl::Value>(content) {
+        Ok(v) => Ok(from_hcl_value(&v)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    Identifier::new(name).is_ok()
+}
+
+// Returns the expression of a given string.
+// A string holding interpolations, like the expressions kept when reading, is a template.
+fn string_expression(s: &str) -> Expression {
+    if !s.contains("${") && !s.contains("%{") {
+        return Expression::String(s.to_owned());
+    }
+
+    let source =
+            match s.strip_prefix("${").and_then(|e| e.strip_suffix('}')) {
+                // A single expression.
+                Some(expr) if !expr.contains("${") => format!("expr = {}\n", expr),
+                _ => format!("expr = {}\n", serde_json::to_string(s).unwrap()),
+            };
+    let parsed = ::hcl::parse(&source).ok()
+        .and_then(|body| body.into_attributes().next())
+        .map(|attr| attr.expr);
+
+    parsed.unwrap_or_else(|| Expression::String(s.to_owned()))
+}
+
+// Returns the expression of a given Value.
+fn to_expression(value: &Value) -> Result<Expression, String> {
+    let expr =
+            match value {
+                Value::Nil => Expression::Null,
+                Value::Boolean(b) => Expression::Bool(*b),
+                Value::Integer(i) => Expression::Number((*i).into()),
+                Value::Float(f) => match Number::from_f64(*f) {
+                    Some(n) => Expression::Number(n),
+                    None => return Err(format!("{} can't be written", f)),
+                },
+                Value::String(s) => string_expression(s),
+                Value::Array(a) => Expression::Array(a.iter().map(to_expression).collect::<Result<_, _>>()?),
+                Value::Object(o) => {
+                    let mut new_obj = ::hcl::Object::new();
+                    for (k, v) in o.iter() {
+                        let key =
+                                match Identifier::new(k.as_str()) {
+                                    Ok(i) => ObjectKey::Identifier(i),
+                                    Err(_) => ObjectKey::Expression(Expression::String(k.clone())),
+                                };
+                        new_obj.insert(key, to_expression(v)?);
+                    } // for
+                    Expression::Object(new_obj)
+                },
+            };
+
+    Ok(expr)
+}
+
+// Tests whether a given Value can be the body of a block: an Object whose keys are identifiers.
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(o) => o.keys().all(|k| is_identifier(k)),
+        _ => false,
+    }
+}
+
+// Creates the body of given entries.
+// With blocks, the objects are blocks and the arrays of objects repeated blocks, otherwise every entry is an attribute.
+fn to_body(entries: &IndexMap<String, Value>, blocks: bool) -> Result<Body, String> {
+    let mut builder = Body::builder();
+    for (k, v) in entries.iter() {
+        let ident =
+                match Identifier::new(k.as_str()) {
+                    Ok(i) => i,
+                    Err(_) => return Err(format!("wrong attribute name '{}'", k)),
+                };
+        builder =
+                match v {
+                    Value::Object(o) if blocks && is_block(v) => {
+                        builder.add_block(Block::builder(ident).add_structures(to_body(o, blocks)?).build())
+                    },
+                    Value::Array(a) if blocks && !a.is_empty() && a.iter().all(is_block) => {
+                        for item in a.iter() {
+                            if let Value::Object(o) = item {
+                                builder = builder.add_block(Block::builder(ident.clone()).add_structures(to_body(o, blocks)?).build());
+                            }
+                        } // for
+                        builder
+                    },
+                    _ => builder.add_attribute(Attribute::new(ident, to_expression(v)?)),
+                };
+    } // for
+
+    Ok(builder.build())
+}
+
+// Writes an Object as an HCL document, either as attributes only or with blocks.
+// The strings holding interpolations are written as expressions.
+pub fn to_string(value: &Value, blocks: bool) -> Result<String, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("hcl requires an object".to_owned()),
+            };
+
+    match ::hcl::to_string(&to_body(o, blocks)?) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    const CONTENT: &str = r#"
+region = "eu-west-1"
+tags = { env = "prod", "a b" = 1 }
+ami = var.ami
+name = "${var.prefix}-web"
+
+resource "aws_instance" "web" {
+  count = 2
+}
+
+service {
+  port = 80
+}
+service {
+  port = 443
+}
+"#;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn it_reads_attributes_and_blocks() {
+            let res = from_str(CONTENT);
+            assert_eq!(res, Ok(new_value(r#"{"region":"eu-west-1","tags":{"env":"prod","a b":1},"ami":"${var.ami}","name":"${var.prefix}-web","resource":{"aws_instance":{"web":{"count":2}}},"service":[{"port":80},{"port":443}]}"#)));
+            assert!(from_str("a = ").is_err());
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_writes_attributes() {
+            let doc = new_value(r#"{"region":"eu-west-1","ami":"${var.ami}","name":"${var.prefix}-web","tags":{"env":"prod","a b":[1,null]}}"#);
+
+            let res = to_string(&doc, false);
+            assert_eq!(res, Ok(concat!(
+                "region = \"eu-west-1\"\n",
+                "ami = var.ami\n",
+                "name = \"${var.prefix}-web\"\n",
+                "tags = {\n",
+                "  env = \"prod\"\n",
+                "  \"a b\" = [\n",
+                "    1,\n",
+                "    null\n",
+                "  ]\n",
+                "}\n",
+            ).to_owned()));
+        }
+
+        #[test]
+        fn it_writes_blocks() {
+            let doc = from_str(CONTENT).unwrap();
+
+            let res = to_string(&doc, true);
+            assert!(res.as_ref().unwrap().contains("service {\n  port = 80\n}\n\nservice {\n  port = 443\n}\n"));
+            assert_eq!(from_str(&res.unwrap()), Ok(doc));
+            assert_eq!(to_string(&new_value(r#"{"a b":1}"#), true), Err("wrong attribute name 'a b'".to_owned()));
+            // The maps are blocks too.
+            assert_eq!(to_string(&new_value(r#"{"tags":{"env":"prod"}}"#), true), Ok("tags {\n  env = \"prod\"\n}\n".to_owned()));
+        }
+    }
+}
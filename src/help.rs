@@ -14,19 +14,49 @@ Available help:
         --help TOPIC                    Print help on a given TOPIC.
 
 Available commands:
-        --check                         Check multiple documents.
+        --canonicalize                  Sort the object keys of multiple documents.
+        --check [OPTION...]             Check multiple documents.
+        --compile-template PATH         Compile a template into Lua code.
         --concat                        Concatenate multiple array-based documents.
     -C, --copy                          Change the format of multiple documents.
+        --delete PATH                   Delete a value of a document.
+        --diff [OPTION...]              Compare two documents.
+        --digest ALGORITHM              Hash the canonical form of multiple documents.
+        --flatten [OPTION...]           Flatten multiple documents into dotted keys.
+        --infer-schema                  Create a JSON schema from multiple documents.
+        --interpolate [OPTION...]       Expand the variables of multiple documents.
+        --json-patch                    Apply a JSON patch to a document.
         --merge [OPTION...]             Merge multiple documents.
+        --merge-patch-diff              Create the merge patch between two documents.
         --pack                          Create one array-based document from multiple documents.
         --unpack                        Create multiple documents from one array-based document.
-    -R, --render PATH                   Render a template with multiple documents.
-    -T, --transform PATH                Transform multiple documents with a Lua script.
+        --query EXPR                    Select values of a document.
+        --query-each EXPR               Select values of a document, as separate documents.
+        --rename OLD=NEW                Rename a value of a document.
+    -R, --render [OPTION...] PATH       Render a template with multiple documents.
+        --render-each [OPTION...] PATH  Render a template once per document.
+        --resolve-refs                  Resolve the references within multiple documents.
+        --set PATH[:HINT]=VALUE         Set a value of a document.
+    -T, --transform [OPTION...] PATH    Transform multiple documents with a Lua script.
+        --unflatten [OPTION...]         Rebuild multiple documents from dotted keys.
+        --validate [OPTION...] SCHEMA   Validate multiple documents against a JSON schema.
 
 Available input/output:
         PATH                            A file. The format is inferred from the extension.
-        --FORMAT [OPTION...] PATH       A file. FORMAT may be plain, json, lua, toml or yaml.
+        --FORMAT [OPTION...] PATH       A file. FORMAT may be plain, env, frontmatter, hcl, html-table, ini, json, lua,
+                                        markdown-table, properties, ron, table, toml or yaml.
     -D, --document HINT VALUE           A inline document. Input only.
+
+Exit codes:
+        0                               Success.
+        1                               Internal error.
+        10                              Wrong parameter.
+        11                              Unknown file format.
+        20                              No input.
+        21                              Wrong input.
+        30                              No output.
+        31                              Wrong output.
+        40                              The documents differ (diff command).
 "#;
 
 pub const TOPIC_HELP: &str = r#"Usage: rmarshal --help TOPIC
@@ -34,29 +64,98 @@ pub const TOPIC_HELP: &str = r#"Usage: rmarshal --help TOPIC
 Interative help.
 
 Available topics:
+    canonicalize        Command to sort the object keys of multiple documents.
     check               Command to check multiple documents.
+    compile-template    Command to compile a template into Lua code.
     concat              Command to concatenate multiple array-based documents.
     copy                Command to change the format of multiple documents.
+    delete              Command to delete a value of a document.
+    diff                Command to compare two documents.
+    digest              Command to hash the canonical form of multiple documents.
     document            Define an inline document.
+    flatten             Command to flatten multiple documents into dotted keys.
+    frontmatter         Define a file with a text document having a front matter.
+    hcl                 Define a file with an HCL document.
+    html-table          Define a file with an HTML table.
+    infer-schema        Command to create a JSON schema from multiple documents.
+    ini                 Define a file with an INI document.
+    interpolate         Command to expand the variables of multiple documents.
+    env                 Define a file with a dotenv document.
     json                Define a file with a JSON document.
+    json-patch          Command to apply a JSON patch to a document.
     lua                 Define a file with a Lua document.
+    markdown-table      Define a file with a Markdown table.
     merge               Command to merge multiple documents.
+    merge-patch-diff    Command to create the merge patch between two documents.
     pack                Command to create one array-based document from multiple documents.
     plain               Define a file with a string-based document.
+    query               Command to select values of a document.
+    query-each          Command to select values of a document, as separate documents.
+    rename              Command to rename a value of a document.
     render              Command to render a template with multiple documents.
+    render-each         Command to render a template once per document.
+    resolve-refs        Command to resolve the references within multiple documents.
+    ron                 Define a file with a RON document.
+    set                 Command to set a value of a document.
+    table               Define a file with an ASCII table.
+    properties          Define a file with a Java properties document.
     toml                Define a file with a TOML document.
     transform           Command to transform multiple documents with a Lua script.
+    unflatten           Command to rebuild multiple documents from dotted keys.
     unpack              Command to create multiple documents from one array-based document.
+    validate            Command to validate multiple documents against a JSON schema.
     yaml                Define a file with YAML document(s).
 "#;
 
-pub const CHECK_HELP: &str = r#"Usage: rmarshal INPUT... --check
+pub const CANONICALIZE_HELP: &str = r#"Usage: rmarshal INPUT... --canonicalize OUTPUT...
 
-Read multiple documents, at least one.
+Read multiple documents.
+Write the same documents, whose object keys are recursively sorted by their
+UTF-16 code units, as of RFC 8785.
+
+Example:
+    cat doc.json
+            {"name":"app","labels":{"tier":"web","app":"app"}}
+    rmarshal doc.json --canonicalize --json -
+            {"labels":{"app":"app","tier":"web"},"name":"app"}
+"#;
+
+pub const CHECK_HELP: &str = r#"Usage: rmarshal INPUT... --check [OPTION...]
+
+Read multiple documents, at least one, with their input options.
 Do not create anything.
+Report every problem of every input, then exit with code 21 if any.
+After another command, only exit once the previous commands succeeded, without options.
+
+Options:
+    --strict                Also report what reading the documents would silently
+                            lose or accept: duplicate keys, non-string YAML keys,
+                            NaN and infinity, tabs in YAML indentation and byte
+                            order marks.
+    --report FORMAT         The report format: text (default) for one line per
+                            problem on stderr, or json for an array of the
+                            problems per input on stdout.
 
 Example:
     rmarshal doc1.json doc2.toml doc3.yaml --check
+    rmarshal doc1.json doc2.yaml --check --strict --report json
+            [{"input":"doc1.json","problems":["duplicate key at '/name'"]},{"input":"doc2.yaml","problems":[]}]
+"#;
+
+pub const COMPILE_TEMPLATE_HELP: &str = r#"Usage: rmarshal --compile-template PATH OUTPUT
+
+Read no document.
+Write one string-based document: the Lua code of the template.
+
+The compiled template may be used in place of the template by the render commands.
+It is plain Lua, whose first comment line locates the errors in the original template.
+
+Compiled templates are also cached in the directory named by the
+RMARSHAL_CACHE_DIR environment variable, if set.
+
+Example:
+    rmarshal --compile-template report --plain report.luac
+    rmarshal data.json --render report.luac out
 "#;
 
 pub const CONCAT_HELP: &str = r#"Usage: rmarshal INPUT... --concat OUTPUT
@@ -77,6 +176,51 @@ Example:
     rmarshal doc1.json doc2.toml doc3.yaml --copy out1.yaml out2.json out3.toml
 "#;
 
+pub const DIFF_HELP: &str = r#"Usage: rmarshal LEFT RIGHT --diff [OPTION...] OUTPUT
+
+Read two documents, in any format.
+Write one document: the differences turning the left document into the right one.
+Exit with code 40 when the documents differ, once the output is written.
+
+Options:
+    --format FORMAT         The output format: listing (default), patch for a JSON
+                            patch (RFC 6902) or merge-patch for a JSON merge patch
+                            (RFC 7396).
+    --ignore-key-order      Objects with the same entries in another order are equal.
+    --ignore-array-order    Arrays with the same elements in another order are equal.
+
+Example:
+    cat doc1.toml
+            name = "app"
+            replicas = 2
+            ports = [80, 443]
+    cat doc2.yaml
+            ---
+            name: app
+            replicas: 3
+            ports:
+              - 80
+    rmarshal doc1.toml doc2.yaml --diff --plain --eol -
+            ~ /replicas: 2 -> 3
+            - /ports/1: 443
+    rmarshal doc1.toml doc2.yaml --diff --format patch --json -
+            [{"op":"replace","path":"/replicas","value":3},{"op":"remove","path":"/ports/1"}]
+"#;
+
+pub const DIGEST_HELP: &str = r#"Usage: rmarshal INPUT... --digest ALGORITHM OUTPUT...
+
+Read multiple documents.
+Write one string-based document per document: the hexadecimal hash of its
+canonical JSON form (RFC 8785), so that the key order, the whitespaces and the
+number formats don't change the hash.
+
+Available algorithms:
+    sha256
+
+Example:
+    rmarshal doc1.json doc2.yaml --digest sha256 --plain --eol - --plain --eol -
+"#;
+
 pub const DOCUMENT_HELP: &str = r#"Usage: rmarshal --document HINT VALUE COMMAND [OUTPUT...]
 
 Define an inline document. Input only.
@@ -100,12 +244,256 @@ Examples:
             Hi
 "#;
 
+pub const EDIT_HELP: &str = r#"Usage: rmarshal INPUT... --set PATH[:HINT]=VALUE OUTPUT...
+       rmarshal INPUT... --delete PATH OUTPUT...
+       rmarshal INPUT... --rename OLD=NEW OUTPUT...
+
+Read multiple documents, at least one.
+Write the same documents, the first one being edited.
+
+A path is either dotted, where '\.' escapes a dot, or a JSON pointer.
+The value is typed like a document, see 'rmarshal --help document'.
+The missing intermediate objects are created, and the other keys keep their position.
+Deleting a missing value does nothing.
+
+Example:
+    cat doc.yaml
+            ---
+            image:
+              name: app
+              tag: "1.0"
+            secret: xyz
+    rmarshal doc.yaml --set image.tag:S=1.2 --delete secret --rename image=container --set /replicas=3 out.yaml
+    cat out.yaml
+            ---
+            container:
+              name: app
+              tag: "1.2"
+            replicas: 3
+"#;
+
+pub const ENV_HELP: &str = r#"Usage: rmarshal --env [OPTION...] PATH COMMAND --env [OPTION...] PATH
+
+Define a file with a dotenv document: a flat object of strings.
+
+The entries are NAME=VALUE lines, with an optional 'export' prefix. The values
+are either unquoted, single-quoted or double-quoted with escapes, and may continue
+on the next line after a trailing backslash. The lines and the unquoted values
+may end with a '#' comment. The written values are quoted when needed.
+
+Available options:
+        --include               Expand the include directives. Input only.
+        --separator SEPARATOR   Nest the keys on input, flatten the nested values on output.
+
+Example:
+    cat .env
+            export DB_HOST=db.local
+            DB_PASSWORD="p@ss \"word\""
+    rmarshal .env --copy --json -
+            {"DB_HOST":"db.local","DB_PASSWORD":"p@ss \"word\""}
+"#;
+
+pub const FLATTEN_HELP: &str = r#"Usage: rmarshal INPUT... --flatten [OPTION...] OUTPUT...
+       rmarshal INPUT... --unflatten [OPTION...] OUTPUT...
+
+Read multiple documents.
+Write the same documents, either flattened or unflattened.
+
+A flattened document is one object whose keys are the paths of the values of
+the original object or array. The empty objects and arrays are kept as values.
+The unflatten command rebuilds the nesting, including the arrays. Without
+brackets, the objects whose keys are the indices from 0 become arrays, so that
+{"a":{"0":1}} round-trips as {"a":[1]}: use --array-style brackets for a
+lossless round-trip. With brackets, indices with gaps remain object keys. The
+key order is preserved.
+
+Options:
+    --separator SEPARATOR   The separator of the keys: . (default) or any other string.
+    --array-style STYLE     The notation of the array indices: index (default) as any
+                            other key, like 'a.0.b', or brackets, like 'a[0].b'.
+
+Example:
+    cat doc.yaml
+            ---
+            image:
+              name: app
+              tag: "1.4"
+            ports:
+              - 80
+              - 443
+    rmarshal doc.yaml --flatten --json -
+            {"image.name":"app","image.tag":"1.4","ports.0":80,"ports.1":443}
+    rmarshal doc.yaml --flatten --separator _ --array-style brackets --json -
+            {"image_name":"app","image_tag":"1.4","ports[0]":80,"ports[1]":443}
+"#;
+
+pub const FRONTMATTER_HELP: &str = r#"Usage: rmarshal --frontmatter [OPTION...] PATH COMMAND --frontmatter [OPTION...] PATH
+
+Define a file with a text document, like Markdown, having a front matter.
+
+The document is {"meta":META,"body":BODY}. META is the front matter: YAML
+between '---' lines or TOML between '+++' lines, and null without one. A front
+matter without entries, such as comments only, is an empty object. BODY is the
+text following the front matter, as is. A leading '---' without a closing line
+is a part of BODY, like a Markdown horizontal rule.
+
+The written document has META as a front matter, unless it is null, followed
+by BODY. The front matter has the format of the first front matter read, or is
+YAML without one.
+
+Available options:
+        --meta FORMAT           The format of the front matter: yaml or toml. Output only.
+
+Example:
+    cat post.md
+            ---
+            title: Hello
+            draft: true
+            ---
+            # Hello
+    rmarshal --frontmatter post.md --set meta.draft=false --frontmatter -
+            ---
+            title: Hello
+            draft: false
+            ---
+            # Hello
+"#;
+
+pub const HCL_HELP: &str = r#"Usage: rmarshal --hcl [OPTION...] PATH COMMAND --hcl [OPTION...] PATH
+
+Define a file with an HCL document, like a Terraform configuration or '.tfvars'.
+
+The attributes are keys and the blocks objects. The labels of a block are
+nested objects, so 'resource "a" "b" { ... }' is {"resource":{"a":{"b":{...}}}}.
+The repeated blocks are an array of their bodies. The expressions which can't
+be evaluated, like references or function calls, are kept as '${...}' strings.
+
+The written document has one attribute per key, whose objects are maps. The
+strings holding '${...}' are written back as expressions, including the plain
+strings of other formats: a YAML 'name: ${prefix}-web' is written as
+'name = "${prefix}-web"', an interpolation of the variable prefix.
+
+Available options:
+        --blocks                Write the objects, and the arrays of objects, whose keys are
+                                identifiers as blocks and repeated blocks. Output only.
+                                Every such object is a block, including the maps of a
+                                '.tfvars' file, like 'tags = { env = "prod" }', which are
+                                invalid as blocks: leave it out for variable files.
+
+Example:
+    cat main.tf
+            region = "eu-west-1"
+            ami = var.ami
+            service "web" {
+              port = 80
+            }
+    rmarshal --hcl main.tf --copy --json -
+            {"region":"eu-west-1","ami":"${var.ami}","service":{"web":{"port":80}}}
+"#;
+
+pub const INFER_SCHEMA_HELP: &str = r#"Usage: rmarshal INPUT... --infer-schema OUTPUT
+
+Read multiple documents, at least one: the samples.
+Write one document: a JSON schema (draft 2020-12) matching every sample.
+
+The schema states the types seen, where null makes a value nullable, the
+item schemas of arrays and the properties of objects. The keys present in
+every sample object are required. The strings are an enum when there are 5
+distinct values at most, and some of them repeat.
+
+Example:
+    cat doc1.json
+            {"name":"app","tier":"web","replicas":2}
+    cat doc2.yaml
+            ---
+            name: db
+            tier: web
+    rmarshal doc1.json doc2.yaml --infer-schema --yaml -
+            ---
+            $schema: "https://json-schema.org/draft/2020-12/schema"
+            type: object
+            properties:
+              name:
+                type: string
+              tier:
+                type: string
+                enum:
+                  - web
+              replicas:
+                type: integer
+            required:
+              - name
+              - tier
+"#;
+
+pub const INI_HELP: &str = r#"Usage: rmarshal --ini [OPTION...] PATH COMMAND --ini [OPTION...] PATH
+
+Define a file with an INI document.
+
+The sections are objects, and the entries before the first section top-level
+keys. A quoted subsection, like [remote "origin"], is an object within its
+section. The entries are 'key = value' or 'key: value' lines, whose values may be
+double-quoted with escapes. The lines and the unquoted values may end with a
+comment. The repeated sections are merged.
+
+The written document has the top-level scalars first, then one section per
+object. The arrays of scalars are repeated keys. The values nested deeper than
+subsections are rejected.
+
+Available options:
+        --comments CHARS        The characters starting a comment. Default is ';#'.
+        --repeated-keys MODE    How the repeated keys are read: last (default) to keep the
+                                last value, array to gather the values or error. Input only.
+        --typed                 Read the unquoted booleans and numbers as such, instead of
+                                strings, and quote the strings that would be read otherwise.
+
+Example:
+    cat config.ini
+            [core]
+            bare = false
+            [remote "origin"]
+            fetch = +refs/heads/*
+            fetch = +refs/tags/*
+    rmarshal --ini --typed --repeated-keys array config.ini --copy --json -
+            {"core":{"bare":false},"remote":{"origin":{"fetch":["+refs/heads/*","+refs/tags/*"]}}}
+"#;
+
+pub const INTERPOLATE_HELP: &str = r#"Usage: rmarshal INPUT... --interpolate [OPTION...] OUTPUT...
+
+Read multiple documents.
+Write the same documents, whose string values have their placeholders expanded:
+    ${VAR}              The value of VAR, or an empty string when missing.
+    ${VAR:-DEFAULT}     DEFAULT when VAR is missing or empty. DEFAULT may hold
+                        placeholders and balanced braces, as in ${A:-${B:-{}}}.
+    ${VAR:?ERROR}       An error when VAR is missing or empty.
+    $${                 A literal ${.
+The ${.PATH} and ${N.PATH} references are kept (see --help resolve-refs).
+
+Options:
+    --vars PATH         Read the variables from an object-based document of
+                        scalars, instead of the environment: the environment
+                        variables are not read at all then.
+    --strict            Fail on missing variables without default.
+    --retype            Type the strings made of a single placeholder like an
+                        inline document of any type (see --help document).
+
+Example:
+    cat doc.yaml
+            ---
+            host: ${DB_HOST:-localhost}
+            port: ${DB_PORT}
+    DB_PORT=5432 rmarshal doc.yaml --interpolate --retype --json -
+            {"host":"localhost","port":5432}
+"#;
+
 pub const JSON_HELP: &str = r#"Usage: rmarshal --json [OPTION...] PATH COMMAND --json [OPTION...] PATH
 
 Define a file with a JSON document.
 
 Available options:
+        --canonical             Activate canonical format (RFC 8785): sorted keys, no whitespaces. Output only.
         --eol                   Add a trailing newline character at the end of each document. Output only.
+        --include               Expand the include directives. Input only.
         --pretty                Activate pretty format. Output only.
     -s, --stream[=LIMIT]        Allow multiple documents within a single file. Output only.
 
@@ -123,12 +511,45 @@ Example:
             }
 "#;
 
+pub const JSON_PATCH_HELP: &str = r#"Usage: rmarshal DOCUMENT PATCH --json-patch OUTPUT
+
+Read two documents: a document and a JSON patch (RFC 6902), in any format.
+Write one document.
+
+The command is named --json-patch since --patch right after --merge is the merge patch
+option of the merge command.
+
+The add, remove, replace, move, copy and test operations are applied atomically:
+on the first failing operation, nothing is written and its index is reported.
+
+Example:
+    cat doc.yaml
+            ---
+            image:
+              name: app
+              tag: "1.0"
+            replicas: 2
+    cat patch.json
+            [
+              {"op": "test", "path": "/image/tag", "value": "1.0"},
+              {"op": "replace", "path": "/image/tag", "value": "1.1"},
+              {"op": "remove", "path": "/replicas"}
+            ]
+    rmarshal doc.yaml patch.json --json-patch out.yaml
+    cat out.yaml
+            ---
+            image:
+              name: app
+              tag: "1.1"
+"#;
+
 pub const LUA_HELP: &str = r#"Usage: rmarshal --lua [OPTION...] PATH COMMAND --lua [OPTION...] PATH
 
 Define a file with a LUA document.
 
 Available options:
         --eol                   Add a trailing newline character at the end of each document. Output only.
+        --include               Expand the include directives. Input only.
     -s, --stream[=LIMIT]        Allow multiple documents within a single file. Output only.
 
 Example:
@@ -142,11 +563,26 @@ Example:
             Object:new({{"msg","Hello"},{"values",Array:new({1,2,3,})},})
 "#;
 
-pub const MERGE_HELP: &str = r#"Usage: rmarshal INPUT... --merge [--depth VALUE] OUTPUT
+pub const MERGE_HELP: &str = r#"Usage: rmarshal INPUT... --merge [--depth VALUE] [--arrays MODE] [--strategy PATH=MODE...] OUTPUT
+       rmarshal INPUT... --merge --patch OUTPUT
+       rmarshal LEFT RIGHT --merge-patch-diff OUTPUT
 
 Read multiple documents, at least one.
 Write one document.
 
+Options:
+    --depth VALUE           The merging depth, infinite when negative (default).
+    --arrays MODE           The merge mode of arrays: merge (default), replace,
+                            append, union or by-key:NAME.
+    --strategy PATH=MODE    The merge mode of the values at a JSON pointer path,
+                            where '*' matches any key or index. Can be repeated.
+    --patch                 Apply the documents as JSON merge patches (RFC 7396):
+                            null removes a key and non-objects replace.
+
+The merge-patch-diff command writes the minimal merge patch turning the left
+document into the right one. Since null removes a key, a null object entry of
+the right document can't be expressed, and is an error.
+
 Example:
     cat doc1.json
             {"msg":"hi","values":{"a":1,"b":2}}
@@ -175,6 +611,26 @@ Example:
               b: 3
               c: 4
             level: 1
+    cat base.yaml
+            ---
+            containers:
+              - name: app
+                image: app:1.0
+              - name: proxy
+                image: proxy:1.0
+    cat prod.yaml
+            ---
+            containers:
+              - name: proxy
+                image: proxy:2.0
+    rmarshal base.yaml prod.yaml --merge --arrays by-key:name out3.yaml
+    cat out3.yaml
+            ---
+            containers:
+              - name: app
+                image: app:1.0
+              - name: proxy
+                image: proxy:2.0
 "#;
 
 pub const PACK_HELP: &str = r#"Usage: rmarshal INPUT... --pack OUTPUT
@@ -204,10 +660,72 @@ Example:
             name = "rmarshal"
 "#;
 
-pub const RENDER_HELP: &str = r#"Usage: rmarshal [INPUT...] --render PATH OUTPUT
+pub const PROPERTIES_HELP: &str = r#"Usage: rmarshal --properties [OPTION...] PATH COMMAND --properties [OPTION...] PATH
+
+Define a file with a Java properties document: a flat object of strings.
+
+The entries are 'key=value', 'key:value' or 'key value' lines, with escapes and
+'\uXXXX' unicode escapes, and may continue on the next line after a trailing
+backslash. The lines starting with '#' or '!' are comments. The written non-ASCII
+characters are unicode escapes.
+
+Available options:
+        --include               Expand the include directives. Input only.
+        --separator SEPARATOR   Nest the keys on input, flatten the nested values on output.
+
+Example:
+    cat doc.yaml
+            ---
+            db:
+              host: db.local
+              port: 5432
+    rmarshal doc.yaml --copy --properties --separator . -
+            db.host=db.local
+            db.port=5432
+"#;
+
+pub const QUERY_HELP: &str = r#"Usage: rmarshal INPUT... --query EXPR OUTPUT
+       rmarshal INPUT... --query-each EXPR [OUTPUT...]
+
+Read one document, the first one.
+Write one array-based document with the selected values (query),
+or one document per selected value (query-each).
+
+The expression is a JSONPath (RFC 9535), where the leading '$' is optional:
+    $.a.b, $['a']       Object entries.
+    $.*, $[*]           Every Object entry or Array element.
+    $[0], $[-1]         Array elements, counted from the end when negative.
+    $[1:5:2]            Array elements from a start to an excluded end by a step.
+    $..a                Object entries at any depth.
+    $[?@.a > 1]         Object entries or Array elements matching a filter, with
+                        ==, !=, <, <=, >, >=, &&, || and !, where @ is the
+                        current value and $ the document.
+
+Example:
+    cat doc.yaml
+            ---
+            spec:
+              containers:
+                - name: app
+                  image: app:1.0
+                - name: proxy
+                  image: proxy:2.0
+    rmarshal doc.yaml --query '.spec.containers[0].image' --json -
+            ["app:1.0"]
+    rmarshal doc.yaml --query-each '$..containers[?@.name != "app"].image' --plain --eol -
+            proxy:2.0
+"#;
+
+pub const RENDER_HELP: &str = r#"Usage: rmarshal [INPUT...] --render [OPTION...] PATH OUTPUT
 
 Read multiple documents, may be none.
-Write one string-based document.
+Write one string-based document, or a document of any FORMAT with --as.
+
+Available options:
+        --as FORMAT             Read the rendered text as a document. FORMAT may be plain, json, lua, toml or yaml.
+        --engine ENGINE         The template engine. ENGINE may be lua or jinja.
+                                By default, jinja for .j2, .jinja and .jinja2 paths, lua otherwise.
+        --strict                Fail on undefined variables and missing object keys.
 
 Example:
     cat data.json
@@ -218,6 +736,120 @@ Example:
     rmarshal data.json --render report out
     cat out
             My name is Althea and I have 10 fingers!
+    cat report.j2
+            My name is {{ inputs[0].name }} and I have {{ inputs[0].fingers }} fingers!
+    rmarshal data.json --render report.j2 out
+    cat out
+            My name is Althea and I have 10 fingers!
+"#;
+
+pub const RENDER_EACH_HELP: &str = r#"Usage: rmarshal [INPUT...] --render-each [OPTION...] PATH [OUTPUT...]
+
+Read multiple documents, may be none.
+Write one string-based document per input document, or documents of any FORMAT with --as.
+
+The current document is available as ctx.input (and ctx:get_input(1)).
+Its index, starting from 1, is available as ctx.index.
+With the jinja engine, they are available as input and index.
+
+Available options:
+        --as FORMAT             Read each rendered text as a document. FORMAT may be plain, json, lua, toml or yaml.
+        --engine ENGINE         The template engine. ENGINE may be lua or jinja.
+        --strict                Fail on undefined variables and missing object keys.
+
+Example:
+    cat hosts.json
+            [{"name":"alfa","ip":"10.0.0.1"},{"name":"bravo","ip":"10.0.0.2"}]
+    cat host
+            <%= ctx.index %>: <%= ctx.input:get('name') %> has address <%= ctx.input:get('ip') %>
+    rmarshal hosts.json --unpack --render-each host out1 out2
+    cat out1
+            1: alfa has address 10.0.0.1
+    cat out2
+            2: bravo has address 10.0.0.2
+"#;
+
+pub const RESOLVE_REFS_HELP: &str = r##"Usage: rmarshal INPUT... --resolve-refs OUTPUT...
+
+Read multiple documents.
+Write the same documents, whose references are replaced by the referenced values:
+    ${.PATH}                        A value of the same document, within a string.
+    ${N.PATH}                       A value of the document N (from 0).
+    {"$ref": "#POINTER"}            A value of the same document.
+    {"$ref": "N#POINTER"}           A value of the document N.
+    $${                             A literal ${.
+
+A PATH is either dotted or a JSON pointer. A string made of a single
+reference is replaced by the referenced value, of any type. Otherwise, the
+referenced value must be a string, a number or a boolean.
+The reference cycles and the dangling references are errors. The other $ref
+objects, to other files or URLs, are kept as is.
+
+Example:
+    cat doc.yaml
+            ---
+            image:
+              name: app
+              tag: "1.4"
+            service:
+              image: ${.image.name}:${.image.tag}
+    rmarshal doc.yaml --resolve-refs --json -
+            {"image":{"name":"app","tag":"1.4"},"service":{"image":"app:1.4"}}
+"##;
+
+pub const RON_HELP: &str = r#"Usage: rmarshal --ron [OPTION...] PATH COMMAND --ron [OPTION...] PATH
+
+Define a file with a RON (Rusty Object Notation) document.
+
+The structs, like Config(port: 80) or (port: 80), and the maps are objects,
+whose map keys are strings. The lists, the tuples, and the tuple structs or
+variants like Point(1, 2) are arrays. None and () are null, and Some(x) is x.
+The unit variants, like Red, are their names. The names of the structs and
+variants are dropped.
+
+The written objects whose keys are identifiers are anonymous structs, the other
+ones maps, and null is None.
+
+Available options:
+        --eol                   Add a trailing newline character at the end of each document. Output only.
+        --include               Expand the include directives. Input only.
+        --pretty                Activate pretty format. Output only.
+
+Example:
+    cat config.ron
+            Config(name: "app", color: Red, limit: Some(10), origin: Point(1, 2))
+    rmarshal config.ron --copy --json -
+            {"name":"app","color":"Red","limit":10,"origin":[1,2]}
+"#;
+
+pub const TABLE_HELP: &str = r#"Usage: rmarshal INPUT... COMMAND --table [OPTION...] PATH
+       rmarshal INPUT... COMMAND --markdown-table [OPTION...] PATH
+       rmarshal INPUT... COMMAND --html-table [OPTION...] PATH
+
+Define a file with a table: aligned ASCII, Markdown or HTML. Output only.
+
+The document is an array of objects, one row per object. The columns are the
+keys of the objects, in the order they are first seen. The missing values and
+null are empty cells, and the arrays and objects are inline JSON.
+
+Available options:
+        --columns NAMES         The comma-separated names of the columns, in order.
+
+Example:
+    cat deps.json
+            [{"name":"serde","version":"1.0.219"},{"name":"indexmap","version":"1.9.3","features":["serde"]}]
+    rmarshal deps.json --copy --table -
+            +----------+---------+-----------+
+            | name     | version | features  |
+            +----------+---------+-----------+
+            | serde    | 1.0.219 |           |
+            | indexmap | 1.9.3   | ["serde"] |
+            +----------+---------+-----------+
+    rmarshal deps.json --copy --markdown-table --columns version,name -
+            | version | name     |
+            | ------- | -------- |
+            | 1.0.219 | serde    |
+            | 1.9.3   | indexmap |
 "#;
 
 pub const TOML_HELP: &str = r#"Usage: rmarshal --toml [OPTION...] PATH COMMAND --toml [OPTION...] PATH
@@ -226,6 +858,7 @@ Define a file with a TOML document.
 
 Available options:
         --fix                   Fix document to circumvent serializer errors.
+        --include               Expand the include directives. Input only.
     -s, --stream[=LIMIT]        Allow multiple documents within a single file. Output only.
 
 Example:
@@ -238,11 +871,14 @@ Example:
             name = "rmarshal"
 "#;
 
-pub const TRANSFORM_HELP: &str = r#"Usage: rmarshal [INPUT...] --transform PATH [OUTPUT...]
+pub const TRANSFORM_HELP: &str = r#"Usage: rmarshal [INPUT...] --transform [OPTION...] PATH [OUTPUT...]
 
 Read multiple documents, may be none.
 Write multiple documents, may be none.
 
+Available options:
+        --strict                Fail on undefined variables and missing object keys.
+
 Example:
     cat doc1.json
             {"value":"hi"}
@@ -277,12 +913,48 @@ Example:
     rmarshal doc.yaml --unpack out1.json out2.toml out3.yaml
 "#;
 
+pub const VALIDATE_HELP: &str = r#"Usage: rmarshal INPUT... --validate [--fill-defaults] SCHEMA OUTPUT...
+
+Read multiple documents, at least one, and a JSON schema in any format.
+Write the same documents when they are all valid.
+Otherwise, list every violation and exit with code 21.
+
+The schema follows the draft 2020-12, unless it states another draft with '$schema'.
+
+Options:
+    --fill-defaults     Fill the documents with the 'default' values of the
+                        'properties' and 'items' subschemas, before validating.
+
+Example:
+    cat schema.yaml
+            ---
+            type: object
+            required: [name]
+            properties:
+              name:
+                type: string
+              replicas:
+                type: integer
+                minimum: 1
+                default: 1
+    cat doc.json
+            {"replicas":0}
+    rmarshal doc.json --validate schema.yaml out.json
+            document 0: '': "name" is a required property (schema '/required')
+            document 0: '/replicas': 0 is less than the minimum of 1 (schema '/properties/replicas/minimum')
+    cat doc2.json
+            {"name":"app"}
+    rmarshal doc2.json --validate --fill-defaults schema.yaml --json -
+            {"name":"app","replicas":1}
+"#;
+
 pub const YAML_HELP: &str = r#"Usage: rmarshal --yaml [OPTION...] PATH COMMAND --yaml [OPTION...] PATH
 
 Define a file with YAML document(s).
 
 Available options:
         --dots                  Add the trailing 3 dots at the end of each document. Output only.
+        --include               Expand the include directives. Input only.
     -s, --stream[=LIMIT]        Allow multiple documents within a single file.
 
 Example:
@@ -0,0 +1,344 @@
+use std::path::{Path, PathBuf};
+use indexmap::IndexMap;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TokenType};
+use crate::patch;
+use crate::unit::FileFormat;
+use crate::value::Value;
+
+const INCLUDE_KEY: &str = "$include";
+const YAML_INCLUDE_TAG: &str = "include";
+
+// The maximum number of nested includes.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+// Includes a document of another file within a Lua document.
+pub const LUA_INCLUDE: &str = r#"
+-- Includes the document of a given file, relative to the including file.
+-- @param path [string]
+-- @return [Object]
+function Context:include(path)
+    return Object:new({{'$include', path}})
+end
+"#;
+
+// A YAML node being parsed, located by the token of its parent.
+struct Frame {
+    token: Option<String>,
+    is_mapping: bool,
+    // For a mapping: the key of the next value, or none when the next node is a key.
+    key: Option<String>,
+    // For a sequence: the index of the next element.
+    next: usize,
+}
+
+// Collects the paths of the '!include' scalars of a YAML document, along with the included paths.
+#[derive(Default)]
+struct TagCollector {
+    frames: Vec<Frame>,
+    // The number of complex keys being parsed.
+    key_depth: usize,
+    includes: Vec<(Vec<String>, String)>,
+}
+
+impl TagCollector {
+    // Returns the token of the next node of the current frame, if it is not a key.
+    fn next_token(&self) -> Option<String> {
+        match self.frames.last() {
+            Some(f) if f.is_mapping => f.key.clone(),
+            Some(f) => Some(f.next.to_string()),
+            None => Some(String::new()),
+        }
+    }
+
+    // Moves to the next node of the current frame, after a given key if the node was a key.
+    fn advance(&mut self, key: Option<String>) {
+        if let Some(f) = self.frames.last_mut() {
+            if !f.is_mapping {
+                f.next += 1;
+            } else if f.key.is_none() {
+                // A complex key, which can't be read anyway, has no name.
+                f.key = Some(key.unwrap_or_default());
+            } else {
+                f.key = None;
+                f.next += 1;
+            }
+        }
+    }
+
+    fn path(&self, token: String) -> Vec<String> {
+        self.frames.iter().skip(1)
+            .filter_map(|f| f.token.clone())
+            .chain(if self.frames.is_empty() { None } else { Some(token) })
+            .collect()
+    }
+}
+
+impl MarkedEventReceiver for TagCollector {
+    fn on_event(&mut self, ev: Event, _: Marker) {
+        match ev {
+            Event::Scalar(value, _, _, tag) => {
+                let token = self.next_token();
+                if let (Some(TokenType::Tag(handle, suffix)), Some(t)) = (tag, &token) {
+                    if handle == "!" && suffix == YAML_INCLUDE_TAG && self.key_depth == 0 {
+                        self.includes.push((self.path(t.clone()), value.clone()));
+                    }
+                }
+                let is_key = token.is_none();
+                self.advance(if is_key { Some(value) } else { None });
+            },
+            Event::Alias(_) => self.advance(None),
+            Event::MappingStart(_) | Event::SequenceStart(_) => {
+                let token = self.next_token();
+                if token.is_none() {
+                    self.key_depth += 1;
+                }
+                self.frames.push(Frame {
+                    token,
+                    is_mapping: matches!(ev, Event::MappingStart(_)),
+                    key: None,
+                    next: 0,
+                });
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                if let Some(f) = self.frames.pop() {
+                    if f.token.is_none() && !self.frames.is_empty() {
+                        self.key_depth -= 1;
+                    }
+                }
+                self.advance(None);
+            },
+            _ => {},
+        }
+    }
+}
+
+// Returns the include directive of a given Object: its only '$include' entry.
+fn include_path(o: &IndexMap<String, Value>) -> Option<&Value> {
+    match o.len() {
+        1 => o.get(INCLUDE_KEY),
+        _ => None,
+    }
+}
+
+// Creates an include directive of a given path.
+fn new_include(path: &str) -> Value {
+    let mut o = IndexMap::new();
+    o.insert(INCLUDE_KEY.to_owned(), Value::String(path.to_owned()));
+
+    Value::Object(o)
+}
+
+// Reads a YAML document whose '!include' scalars are replaced by include directives.
+fn read_yaml(content: &str) -> Result<Value, String> {
+    let mut value = crate::read_document(&FileFormat::Yaml, content)?;
+
+    let mut collector = TagCollector::default();
+    if let Err(e) = Parser::new(content.chars()).load(&mut collector, false) {
+        return Err(e.to_string());
+    }
+    for (path, included) in collector.includes.into_iter() {
+        match patch::get_mut(&mut value, &path) {
+            Some(v) => *v = new_include(&included),
+            None => return Err(format!("wrong include at '{}'", patch::to_pointer(&path))),
+        }
+    } // for
+
+    Ok(value)
+}
+
+// The files being included.
+struct Includes {
+    // The canonical directory of the input file, out of which nothing is included.
+    root: PathBuf,
+    // The canonical paths of the input file and of the files being included.
+    stack: Vec<PathBuf>,
+    // The number of files being included, the input file aside.
+    depth: usize,
+}
+
+// Expands the include directives of a given Value, relative to a given directory.
+fn expand(value: &mut Value, dir: &Path, includes: &mut Includes) -> Result<(), String> {
+    match value {
+        Value::Object(o) => {
+            if let Some(included) = include_path(o) {
+                let included =
+                        match included {
+                            Value::String(p) if Path::new(p).is_absolute() => return Err(format!("cannot include '{}': absolute path", p)),
+                            Value::String(p) => dir.join(p),
+                            _ => return Err(format!("'{}' is not a string", INCLUDE_KEY)),
+                        };
+                *value = include(&included, includes)?;
+                return Ok(());
+            }
+
+            for v in o.values_mut() {
+                expand(v, dir, includes)?;
+            } // for
+        },
+        Value::Array(a) => {
+            for v in a.iter_mut() {
+                expand(v, dir, includes)?;
+            } // for
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+// Reads the document of a given included file, whose include directives are expanded.
+fn include(path: &Path, includes: &mut Includes) -> Result<Value, String> {
+    let path =
+            match std::fs::canonicalize(path) {
+                Ok(p) => p,
+                Err(e) => return Err(format!("cannot include '{}': {}", path.display(), e)),
+            };
+    // The '..' components and the symbolic links can't reach the rest of the file system.
+    if !path.starts_with(&includes.root) {
+        return Err(format!("cannot include '{}': outside of '{}'", path.display(), includes.root.display()));
+    }
+    if includes.stack.contains(&path) {
+        let cycle: Vec<String> = includes.stack.iter().chain(Some(&path)).map(|p| p.display().to_string()).collect();
+        return Err(format!("include cycle {}", cycle.join(" -> ")));
+    }
+    if includes.depth >= MAX_INCLUDE_DEPTH {
+        return Err(format!("cannot include '{}': more than {} nested includes", path.display(), MAX_INCLUDE_DEPTH));
+    }
+    // A file of another format would be included as a string.
+    let format = FileFormat::for_path(&path.to_string_lossy());
+    if format == FileFormat::Plain {
+        return Err(format!("cannot include '{}': unknown format", path.display()));
+    }
+
+    let content =
+            match std::fs::read_to_string(&path) {
+                Ok(c) => c,
+                Err(e) => return Err(format!("cannot include '{}': {}", path.display(), e)),
+            };
+    let mut value =
+            match parse(&format, &content) {
+                Ok(v) => v,
+                Err(e) => return Err(format!("{}: {}", path.display(), e)),
+            };
+    includes.stack.push(path.clone());
+    includes.depth += 1;
+    let res = expand(&mut value, path.parent().unwrap_or(Path::new("")), includes).map(|_| value);
+    includes.depth -= 1;
+    includes.stack.pop();
+
+    res
+}
+
+// Reads a document of a given format, where YAML '!include' scalars are include directives.
+fn parse(format: &FileFormat, content: &str) -> Result<Value, String> {
+    match format {
+        FileFormat::Yaml => read_yaml(content),
+        _ => crate::read_document(format, content),
+    }
+}
+
+// Reads a document of a given format, read from a file at a given path, whose includes are expanded:
+// - the '!include PATH' YAML scalars,
+// - the '{"$include": "PATH"}' objects,
+// - the 'ctx:include(PATH)' Lua calls.
+// The included paths are relative to the including file, or to the current directory for stdin.
+// They can't be absolute, nor reach a file out of the directory of the input file.
+pub fn read_document(format: &FileFormat, content: &str, path: &str) -> Result<Value, String> {
+    let mut stack = Vec::new();
+    let dir =
+            if path == crate::STDIO_PLACEHOLDER {
+                PathBuf::new()
+            } else {
+                if let Ok(p) = std::fs::canonicalize(path) {
+                    stack.push(p);
+                }
+                Path::new(path).parent().map(|p| p.to_path_buf()).unwrap_or_default()
+            };
+    let root =
+            match std::fs::canonicalize(if dir.as_os_str().is_empty() { Path::new(".") } else { &dir }) {
+                Ok(r) => r,
+                Err(e) => return Err(format!("cannot include from '{}': {}", dir.display(), e)),
+            };
+    let mut includes = Includes {
+        root,
+        stack,
+        depth: 0,
+    };
+
+    let mut value = parse(format, content)?;
+    expand(&mut value, &dir, &mut includes)?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod read_yaml {
+        use super::*;
+
+        #[test]
+        fn it_marks_include_tags() {
+            let content = "---\na: !include a.yaml\nb:\n  - 1\n  - !include 'b c.json'\n  - {d: !include d.toml}\n1: !include e.yaml\n";
+
+            let res = read_yaml(content).unwrap();
+            assert_eq!(serde_json::to_string(&res).unwrap(), concat!(
+                r#"{"a":{"$include":"a.yaml"},"#,
+                r#""b":[1,{"$include":"b c.json"},{"d":{"$include":"d.toml"}}],"#,
+                r#""1":{"$include":"e.yaml"}}"#,
+            ));
+        }
+    }
+
+    mod read_document {
+        use super::*;
+
+        const DATA_DIR: &str = "test/data/include";
+
+        #[test]
+        fn it_expands_includes() {
+            let path = format!("{}/sub/service.toml", DATA_DIR);
+            let content = std::fs::read_to_string(&path).unwrap();
+
+            let res = read_document(&FileFormat::Toml, &content, &path).unwrap();
+            assert_eq!(serde_json::to_string(&res).unwrap(), r#"{"image":{"name":"app","tag":"1.4"}}"#);
+        }
+
+        #[test]
+        fn it_rejects_absolute_paths() {
+            let res = read_document(&FileFormat::Json, r#"{"a":{"$include":"/etc/hosts"}}"#, "-");
+            assert_eq!(res, Err("cannot include '/etc/hosts': absolute path".to_owned()));
+        }
+
+        #[test]
+        fn it_rejects_paths_out_of_the_input_directory() {
+            let path = format!("{}/sub/db.json", DATA_DIR);
+
+            let res = read_document(&FileFormat::Json, r#"{"$include":"../input01.yaml"}"#, &path);
+            assert!(res.unwrap_err().contains("input01.yaml': outside of '"));
+        }
+
+        #[test]
+        fn it_limits_nested_includes() {
+            // Each file includes the next one: d01 includes 16 files down to d17.
+            let path = format!("{}/depth/d01.json", DATA_DIR);
+            let content = std::fs::read_to_string(&path).unwrap();
+
+            let res = read_document(&FileFormat::Json, &content, &path);
+            assert_eq!(res, Ok(Value::Integer(17)));
+            let content = format!(r#"{{"$include":"{}"}}"#, path);
+            let res = read_document(&FileFormat::Json, &content, "-");
+            assert!(res.unwrap_err().ends_with("d17.json': more than 16 nested includes"));
+        }
+
+        #[test]
+        fn it_rejects_unknown_formats() {
+            let path = format!("{}/input01.yaml", DATA_DIR);
+
+            let res = read_document(&FileFormat::Json, r#"{"$include":"sub"}"#, &path);
+            assert!(res.unwrap_err().ends_with("sub': unknown format"));
+        }
+    }
+}
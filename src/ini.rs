@@ -0,0 +1,413 @@
+use indexmap::IndexMap;
+use crate::patch;
+use crate::value::{self, Value};
+
+const DEFAULT_COMMENTS: &str = ";#";
+
+// How the repeated keys of a section are read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatedKeys {
+    // The last value wins.
+    #[default]
+    Last,
+    // The values become an array.
+    Array,
+    // A repeated key is an error.
+    Error,
+}
+
+impl RepeatedKeys {
+    pub fn for_str(mode: &str) -> Result<Self, ()> {
+        match mode {
+            "last" => Ok(RepeatedKeys::Last),
+            "array" => Ok(RepeatedKeys::Array),
+            "error" => Ok(RepeatedKeys::Error),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniOptions {
+    // The characters starting a comment.
+    pub comments: String,
+    pub repeated_keys: RepeatedKeys,
+    // The unquoted booleans and numbers are typed, instead of strings.
+    pub typed: bool,
+}
+
+impl Default for IniOptions {
+    fn default() -> Self {
+        IniOptions {
+            comments: DEFAULT_COMMENTS.to_owned(),
+            repeated_keys: RepeatedKeys::default(),
+            typed: false,
+        }
+    }
+}
+
+// Returns the typed Value of a given unquoted value.
+fn typed_value(value: &str) -> Value {
+    if value.eq_ignore_ascii_case("true") {
+        Value::Boolean(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Value::Boolean(false)
+    } else if let Ok(i) = value.parse::<i64>() {
+        Value::Integer(i)
+    } else if value.contains(|c: char| c.is_ascii_digit()) && value.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c)) {
+        value.parse::<f64>().map(Value::Float).unwrap_or_else(|_| Value::String(value.to_owned()))
+    } else {
+        Value::String(value.to_owned())
+    }
+}
+
+// Reads a double-quoted value, after its opening quote, and returns it with the rest of the line.
+fn read_quoted(content: &str) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = content.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &content[idx + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, e)) => value.push(e),
+                None => break,
+            },
+            _ => value.push(c),
+        }
+    } // while
+
+    Err("unterminated quote".to_owned())
+}
+
+// Returns a given line without its trailing comment, which starts a line or follows a whitespace.
+fn strip_comment<'a>(line: &'a str, comments: &str) -> &'a str {
+    let mut prev = ' ';
+    for (idx, c) in line.char_indices() {
+        if comments.contains(c) && prev.is_whitespace() {
+            return &line[..idx];
+        }
+        prev = c;
+    } // for
+
+    line
+}
+
+// Returns the Value of a given raw value.
+fn read_value(raw: &str, options: &IniOptions) -> Result<Value, String> {
+    match raw.strip_prefix('"') {
+        Some(quoted) => {
+            let (value, rest) = read_quoted(quoted)?;
+            if !strip_comment(rest, &options.comments).trim().is_empty() {
+                return Err(format!("unexpected '{}' after the quoted value", rest.trim()));
+            }
+            Ok(Value::String(value))
+        },
+        None => {
+            let value = strip_comment(raw, &options.comments).trim_end();
+            if options.typed {
+                Ok(typed_value(value))
+            } else {
+                Ok(Value::String(value.to_owned()))
+            }
+        },
+    }
+}
+
+// Returns the path of a given section header: its name, and its quoted subsection if any.
+fn read_header(header: &str) -> Result<Vec<String>, String> {
+    let inner =
+            match header.strip_suffix(']') {
+                Some(i) => i[1..].trim(),
+                None => return Err("wrong section".to_owned()),
+            };
+    let path =
+            match inner.split_once(" \"") {
+                Some((name, sub)) => match sub.strip_suffix('"') {
+                    Some(s) => vec![name.trim_end().to_owned(), s.replace("\\\"", "\"").replace("\\\\", "\\")],
+                    None => return Err("wrong section".to_owned()),
+                },
+                None => vec![inner.to_owned()],
+            };
+    if path[0].is_empty() {
+        return Err("missing section name".to_owned());
+    }
+
+    Ok(path)
+}
+
+// Reads an INI document: the sections become objects, and the global entries top-level keys.
+// A quoted subsection, like '[remote "origin"]', is an object within its section.
+pub fn from_str(content: &str, options: &IniOptions) -> Result<Value, String> {
+    let mut root = Value::Object(IndexMap::new());
+    let mut section: Vec<String> = Vec::new();
+    for (idx, line) in content.strip_prefix('\u{FEFF}').unwrap_or(content).lines().enumerate() {
+        let line_nb = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(|c| options.comments.contains(c)) {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            section = read_header(strip_comment(line, &options.comments).trim_end()).map_err(|e| format!("line {}: {}", line_nb, e))?;
+            let mut target = &mut root;
+            for token in section.iter() {
+                target =
+                        match target {
+                            Value::Object(o) => o.entry(token.clone()).or_insert_with(|| Value::Object(IndexMap::new())),
+                            _ => unreachable!(),
+                        };
+                if !matches!(target, Value::Object(_)) {
+                    return Err(format!("line {}: conflicting section '{}'", line_nb, token));
+                }
+            } // for
+            continue;
+        }
+
+        let sep =
+                match line.find(['=', ':']) {
+                    Some(s) => s,
+                    None => return Err(format!("line {}: missing '='", line_nb)),
+                };
+        let key = line[..sep].trim_end();
+        if key.is_empty() {
+            return Err(format!("line {}: missing key", line_nb));
+        }
+        let value = read_value(line[sep + 1..].trim_start(), options).map_err(|e| format!("line {}: {}", line_nb, e))?;
+
+        let entries =
+                match patch::get_mut(&mut root, &section) {
+                    Some(Value::Object(o)) => o,
+                    _ => unreachable!(),
+                };
+        match entries.get_mut(key) {
+            None => {
+                entries.insert(key.to_owned(), value);
+            },
+            Some(Value::Object(_)) => return Err(format!("line {}: conflicting key '{}'", line_nb, key)),
+            Some(existing) => match options.repeated_keys {
+                RepeatedKeys::Last => *existing = value,
+                RepeatedKeys::Array => match existing {
+                    Value::Array(a) => a.push(value),
+                    _ => *existing = Value::Array(vec![existing.clone(), value]),
+                },
+                RepeatedKeys::Error => return Err(format!("line {}: repeated key '{}'", line_nb, key)),
+            },
+        }
+    } // for
+
+    Ok(root)
+}
+
+// Returns a given scalar, quoted when it would not be read as is.
+fn format_value(value: &Value, options: &IniOptions) -> Option<String> {
+    let s = value::scalar_to_string(value)?;
+    let is_retyped = options.typed && matches!(value, Value::String(_)) && !matches!(typed_value(&s), Value::String(_));
+    if !is_retyped
+        && s.trim() == s
+        && !s.starts_with('"')
+        && !s.contains(['\n', '\r'])
+        && strip_comment(&s, &options.comments) == s {
+        return Some(s);
+    }
+
+    let mut sb = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\n' => sb.push_str("\\n"),
+            '\r' => sb.push_str("\\r"),
+            '\t' => sb.push_str("\\t"),
+            '\\' | '"' => {
+                sb.push('\\');
+                sb.push(c);
+            },
+            _ => sb.push(c),
+        }
+    } // for
+    sb.push('"');
+
+    Some(sb)
+}
+
+// Writes the scalar entries of a given Object located at a given path, where arrays are repeated keys.
+// The objects are left to the caller.
+fn write_entries(entries: &IndexMap<String, Value>, path: &mut Vec<String>, options: &IniOptions, sb: &mut String) -> Result<(), String> {
+    for (k, v) in entries.iter() {
+        if k.is_empty()
+            || k.trim() != k
+            || k.starts_with(|c| options.comments.contains(c) || c == '[')
+            || k.contains(['=', ':', '\n', '\r']) {
+            return Err(format!("wrong key '{}'", k));
+        }
+        let values =
+                match v {
+                    Value::Object(_) => continue,
+                    Value::Array(a) => a.iter().collect(),
+                    _ => vec![v],
+                };
+        for value in values.into_iter() {
+            match format_value(value, options) {
+                Some(s) => {
+                    sb.push_str(k);
+                    sb.push_str(" =");
+                    if !s.is_empty() {
+                        sb.push(' ');
+                        sb.push_str(&s);
+                    }
+                    sb.push('\n');
+                },
+                None => {
+                    path.push(k.clone());
+                    return Err(format!("nested value at '{}'", patch::to_pointer(path)));
+                },
+            }
+        } // for
+    } // for
+
+    Ok(())
+}
+
+// Writes a section of given entries and path, and its subsections.
+fn write_section(entries: &IndexMap<String, Value>, path: &mut Vec<String>, options: &IniOptions, sb: &mut String) -> Result<(), String> {
+    let name = &path[0];
+    if name.is_empty() || name.contains(['[', ']', '"', '\n', '\r']) {
+        return Err(format!("wrong section name '{}'", name));
+    }
+    // A section made of subsections only has no header of its own.
+    if entries.is_empty() || entries.values().any(|v| !matches!(v, Value::Object(_))) {
+        if !sb.is_empty() {
+            sb.push('\n');
+        }
+        match path.get(1) {
+            Some(sub) => sb.push_str(&format!("[{} \"{}\"]\n", name, sub.replace('\\', "\\\\").replace('"', "\\\""))),
+            None => sb.push_str(&format!("[{}]\n", name)),
+        }
+        write_entries(entries, path, options, sb)?;
+    }
+
+    for (k, v) in entries.iter() {
+        if let Value::Object(o) = v {
+            path.push(k.clone());
+            if path.len() > 2 || k.contains(['\n', '\r']) {
+                return Err(format!("nested value at '{}'", patch::to_pointer(path)));
+            }
+            write_section(o, path, options, sb)?;
+            path.pop();
+        }
+    } // for
+
+    Ok(())
+}
+
+// Writes an INI document: the global entries first, then one section per object.
+// The objects within a section are quoted subsections, and can't be nested deeper.
+pub fn to_string(value: &Value, options: &IniOptions) -> Result<String, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("ini requires an object".to_owned()),
+            };
+
+    let mut sb = String::new();
+    let mut path = Vec::new();
+    write_entries(o, &mut path, options, &mut sb)?;
+    for (k, v) in o.iter() {
+        if let Value::Object(section) = v {
+            path.push(k.clone());
+            write_section(section, &mut path, options, &mut sb)?;
+            path.pop();
+        }
+    } // for
+
+    Ok(sb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod from_str {
+        use super::*;
+
+        const CONTENT: &str = concat!(
+            "; global\n",
+            "name = app\n",
+            "\n",
+            "[core]\n",
+            "bare = false ; comment\n",
+            "  path = \"C:\\\\app ; x\" # comment\n",
+            "[remote \"origin\"]\n",
+            "url = git@host:app.git\n",
+            "fetch = +refs/heads/*\n",
+            "fetch = +refs/tags/*\n",
+            "[core]\n",
+            "level: 1.5\n",
+        );
+
+        #[test]
+        fn it_reads_sections() {
+            let res = from_str(CONTENT, &IniOptions::default());
+            assert_eq!(res, Ok(new_value(r#"{"name":"app","core":{"bare":"false","path":"C:\\app ; x","level":"1.5"},"remote":{"origin":{"url":"git@host:app.git","fetch":"+refs/tags/*"}}}"#)));
+        }
+
+        #[test]
+        fn it_reads_typed_arrays() {
+            let options = IniOptions {
+                repeated_keys: RepeatedKeys::Array,
+                typed: true,
+                ..IniOptions::default()
+            };
+
+            let res = from_str(CONTENT, &options);
+            assert_eq!(res, Ok(new_value(r#"{"name":"app","core":{"bare":false,"path":"C:\\app ; x","level":1.5},"remote":{"origin":{"url":"git@host:app.git","fetch":["+refs/heads/*","+refs/tags/*"]}}}"#)));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_lines() {
+            let options = IniOptions {
+                repeated_keys: RepeatedKeys::Error,
+                ..IniOptions::default()
+            };
+            assert_eq!(from_str("[a]\nb = 1\nb = 2\n", &options), Err("line 3: repeated key 'b'".to_owned()));
+            assert_eq!(from_str("[a\n", &options), Err("line 1: wrong section".to_owned()));
+            assert_eq!(from_str("a = 1\n[a]\n", &options), Err("line 2: conflicting section 'a'".to_owned()));
+            assert_eq!(from_str("a\n", &options), Err("line 1: missing '='".to_owned()));
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_writes_sections() {
+            let doc = new_value(r#"{"remote":{"origin":{"url":"x"}},"core":{"bare":false},"name":"app","tags":[1,"a ;b"],"empty":null,"none":{}}"#);
+
+            let res = to_string(&doc, &IniOptions::default());
+            assert_eq!(res, Ok("name = app\ntags = 1\ntags = \"a ;b\"\nempty =\n\n[remote \"origin\"]\nurl = x\n\n[core]\nbare = false\n\n[none]\n".to_owned()));
+        }
+
+        #[test]
+        fn it_quotes_retyped_strings() {
+            let options = IniOptions {
+                typed: true,
+                ..IniOptions::default()
+            };
+            let doc = new_value(r#"{"a":{"b":"1","c":1,"d":" x"}}"#);
+
+            let res = to_string(&doc, &options);
+            assert_eq!(res, Ok("[a]\nb = \"1\"\nc = 1\nd = \" x\"\n".to_owned()));
+            assert_eq!(from_str(&res.unwrap(), &options), Ok(doc));
+        }
+
+        #[test]
+        fn it_fails_on_deep_values() {
+            let res = to_string(&new_value(r#"{"a":{"b":{"c":{"d":1}}}}"#), &IniOptions::default());
+            assert_eq!(res, Err("nested value at '/a/b/c'".to_owned()));
+            let res = to_string(&new_value(r#"{"a":[{"b":1}]}"#), &IniOptions::default());
+            assert_eq!(res, Err("nested value at '/a'".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::patch;
+use crate::unit::DocumentHint;
+use crate::value::Value;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InterpolateOptions {
+    // Missing variables without default are errors, instead of empty strings.
+    pub strict: bool,
+    // A string made of a single placeholder is typed like an inline document of any type.
+    pub retype: bool,
+}
+
+// Returns the position of the '}' closing the placeholder starting a given string, skipping the nested braces.
+fn placeholder_end(content: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in content.char_indices().skip(2) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(idx),
+            '}' => depth -= 1,
+            _ => {},
+        }
+    } // for
+
+    None
+}
+
+// Expands the placeholders of a given string:
+// - '${VAR}' to the value of VAR,
+// - '${VAR:-DEFAULT}' to DEFAULT when VAR is missing or empty, DEFAULT having its own placeholders expanded,
+// - '${VAR:?ERROR}' to an error when VAR is missing or empty,
+// - '$${' to '${'.
+// The '${.PATH}' and '${N.PATH}' references are kept.
+pub fn interpolate_str<F>(content: &str, lookup: &F, strict: bool) -> Result<String, String>
+where F: Fn(&str) -> Option<String> {
+    let mut sb = String::new();
+    let mut rest = content;
+    while let Some(pos) = rest.find('$') {
+        sb.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if rest.starts_with("$${") {
+            sb.push_str("${");
+            rest = &rest[3..];
+            continue;
+        }
+        if !rest.starts_with("${") {
+            sb.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+
+        let end =
+                match placeholder_end(rest) {
+                    Some(e) => e,
+                    None => return Err(format!("unterminated placeholder '{}'", rest)),
+                };
+        let expr = &rest[2..end];
+        let placeholder = &rest[..end + 1];
+        rest = &rest[end + 1..];
+
+        // The references to documents are left to the resolve-refs command.
+        if expr.trim_start_matches(|c: char| c.is_ascii_digit()).starts_with('.') {
+            sb.push_str(placeholder);
+            continue;
+        }
+
+        let (name, modifier) =
+                match expr.find(':') {
+                    Some(idx) if expr[idx + 1..].starts_with(['-', '?']) => (&expr[..idx], Some((&expr[idx + 1..idx + 2], &expr[idx + 2..]))),
+                    _ => (expr, None),
+                };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("wrong variable name '{}'", name));
+        }
+
+        let value = lookup(name).filter(|v| !v.is_empty() || modifier.is_none());
+        match (value, modifier) {
+            (Some(v), _) => sb.push_str(&v),
+            (None, Some(("-", default))) => sb.push_str(&interpolate_str(default, lookup, strict)?),
+            (None, Some((_, error))) => return Err(format!("{}: {}", name, if error.is_empty() { "missing variable" } else { error })),
+            (None, None) => {
+                if strict {
+                    return Err(format!("undefined variable '{}'", name));
+                }
+            },
+        }
+    } // while
+    sb.push_str(rest);
+
+    Ok(sb)
+}
+
+// Tests whether a given string is made of a single placeholder.
+fn is_placeholder(content: &str) -> bool {
+    content.starts_with("${") && placeholder_end(content) == Some(content.len() - 1)
+}
+
+// Expands the placeholders of the strings of a given Value located at a given path.
+fn interpolate_value<F>(value: &mut Value, lookup: &F, options: &InterpolateOptions, path: &mut Vec<String>) -> Result<(), String>
+where F: Fn(&str) -> Option<String> {
+    match value {
+        Value::String(s) => {
+            let expanded =
+                    match interpolate_str(s, lookup, options.strict) {
+                        Ok(e) => e,
+                        Err(e) => return Err(format!("'{}': {}", patch::to_pointer(path), e)),
+                    };
+            *value =
+                    if options.retype && is_placeholder(s) {
+                        crate::create_document(DocumentHint::Any, &expanded).unwrap_or(Value::String(expanded))
+                    } else {
+                        Value::String(expanded)
+                    };
+        },
+        Value::Array(a) => {
+            for (idx, v) in a.iter_mut().enumerate() {
+                path.push(idx.to_string());
+                interpolate_value(v, lookup, options, path)?;
+                path.pop();
+            } // for
+        },
+        Value::Object(o) => {
+            for (k, v) in o.iter_mut() {
+                path.push(k.clone());
+                interpolate_value(v, lookup, options, path)?;
+                path.pop();
+            } // for
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+// Creates a new Value by expanding the placeholders of every string of a given document.
+// The variables are looked up by a given function.
+pub fn interpolate<F>(doc: &Value, lookup: &F, options: &InterpolateOptions) -> Result<Value, String>
+where F: Fn(&str) -> Option<String> {
+    let mut res = doc.clone();
+    interpolate_value(&mut res, lookup, options, &mut Vec::new())?;
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("db.local".to_owned()),
+            "PORT" => Some("5432".to_owned()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    mod interpolate_str {
+        use super::*;
+
+        #[test]
+        fn it_expands_placeholders() {
+            let res = interpolate_str("${HOST}:${PORT}/${NAME:-app}?${EMPTY:-x}${EMPTY}", &lookup, false);
+            assert_eq!(res, Ok("db.local:5432/app?x".to_owned()));
+            assert_eq!(interpolate_str("$${HOST} costs $5", &lookup, true), Ok("${HOST} costs $5".to_owned()));
+            assert_eq!(interpolate_str("${.a.b}/${1.c}", &lookup, true), Ok("${.a.b}/${1.c}".to_owned()));
+        }
+
+        #[test]
+        fn it_expands_nested_defaults() {
+            assert_eq!(interpolate_str("${NAME:-${HOST}}:${PORT}", &lookup, true), Ok("db.local:5432".to_owned()));
+            assert_eq!(interpolate_str("${NAME:-${USER:-${EMPTY:-x}}}", &lookup, false), Ok("x".to_owned()));
+            assert_eq!(interpolate_str("${NAME:-{}}", &lookup, false), Ok("{}".to_owned()));
+            assert_eq!(interpolate_str("${HOST:-{}}", &lookup, false), Ok("db.local".to_owned()));
+            assert!(interpolate_str("${NAME:-${HOST}", &lookup, false).is_err());
+        }
+
+        #[test]
+        fn it_fails_on_missing_variables() {
+            assert_eq!(interpolate_str("${NAME}", &lookup, false), Ok(String::new()));
+            assert_eq!(interpolate_str("${NAME}", &lookup, true), Err("undefined variable 'NAME'".to_owned()));
+            assert_eq!(interpolate_str("${EMPTY:?is required}", &lookup, false), Err("EMPTY: is required".to_owned()));
+            assert!(interpolate_str("${HOST", &lookup, false).is_err());
+            assert!(interpolate_str("${HOST:x}", &lookup, false).is_err());
+        }
+    }
+
+    mod interpolate {
+        use super::*;
+
+        #[test]
+        fn it_retypes_placeholders() {
+            let doc = new_value(r#"{"db":{"host":"${HOST}","port":"${PORT}","url":"${HOST}:${PORT}"},"debug":"${DEBUG:-false}"}"#);
+            let options = InterpolateOptions {
+                retype: true,
+                ..InterpolateOptions::default()
+            };
+
+            let res = interpolate(&doc, &lookup, &options);
+            assert_eq!(res, Ok(new_value(r#"{"db":{"host":"db.local","port":5432,"url":"db.local:5432"},"debug":false}"#)));
+        }
+
+        #[test]
+        fn it_locates_errors() {
+            let doc = new_value(r#"{"db":["${HOST:?}"]}"#);
+
+            let res = interpolate(&doc, &|_: &str| None, &InterpolateOptions::default());
+            assert_eq!(res, Err("'/db/0': HOST: missing variable".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use minijinja::{
+    Environment,
+    Error as JinjaError,
+    UndefinedBehavior,
+    Value as JinjaValue,
+};
+use crate::value::{self, Value};
+
+// Serializes a value into YAML.
+fn toyaml(value: JinjaValue) -> Result<String, JinjaError> {
+    match serde_yaml::to_string(&value) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(JinjaError::new(minijinja::ErrorKind::InvalidOperation, e.to_string())),
+    }
+}
+
+// Serializes a value into TOML.
+fn totoml(value: JinjaValue) -> Result<String, JinjaError> {
+    match toml::to_string(&value) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(JinjaError::new(minijinja::ErrorKind::InvalidOperation, e.to_string())),
+    }
+}
+
+// Serializes a value into Lua.
+fn tolua(value: JinjaValue) -> Result<String, JinjaError> {
+    let json =
+            match serde_json::to_string(&value) {
+                Ok(s) => s,
+                Err(e) => return Err(JinjaError::new(minijinja::ErrorKind::InvalidOperation, e.to_string())),
+            };
+    match value::from_json_str(&json) {
+        Ok(v) => Ok(value::to_lua_string(&v)),
+        Err(e) => Err(JinjaError::new(minijinja::ErrorKind::InvalidOperation, e)),
+    }
+}
+
+pub struct JinjaTemplate {
+    // The template path.
+    pub path: String,
+    // The template source.
+    source: String,
+}
+
+impl JinjaTemplate {
+    pub fn for_path(path: &str) -> Self {
+        let source =
+                match std::fs::read_to_string(path) {
+                    Ok(c) => c,
+                    Err(e) => panic!("{}", e),
+                };
+
+        JinjaTemplate {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    // Returns the message of a given error, located in the template.
+    fn error_message(&self, err: &JinjaError) -> String {
+        let msg =
+                match err.detail() {
+                    Some(d) => format!("{}: {}", err.kind(), d),
+                    None => err.kind().to_string(),
+                };
+        match err.line() {
+            Some(line) => format!("{}:{}: {}", self.path, line, msg),
+            None => format!("{}: {}", self.path, msg),
+        }
+    }
+
+    // Renders the template with given input documents.
+    // The inputs are available as 'inputs'.
+    // When rendering once per document, the document and its index are available as 'input' and 'index'.
+    pub fn render(&self, inputs: &[Value], index: Option<usize>, strict: bool) -> Result<String, String> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        if strict {
+            env.set_undefined_behavior(UndefinedBehavior::Strict);
+        }
+        env.add_filter("toyaml", toyaml);
+        env.add_filter("totoml", totoml);
+        env.add_filter("tolua", tolua);
+
+        let template =
+                match env.template_from_named_str(&self.path, &self.source) {
+                    Ok(t) => t,
+                    Err(e) => return Err(self.error_message(&e)),
+                };
+
+        let ctx =
+                match index {
+                    Some(idx) => minijinja::context! {
+                        inputs => JinjaValue::from_serialize(inputs),
+                        input => JinjaValue::from_serialize(&inputs[0]),
+                        index => idx,
+                    },
+                    None => minijinja::context! {
+                        inputs => JinjaValue::from_serialize(inputs),
+                    },
+                };
+
+        match template.render(ctx) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(self.error_message(&e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn new_template(source: &str) -> JinjaTemplate {
+        JinjaTemplate {
+            path: "tpl.j2".to_owned(),
+            source: source.to_owned(),
+        }
+    }
+
+    fn new_input() -> Value {
+        let mut o = IndexMap::new();
+        o.insert("name".to_owned(), Value::String("Althea".to_owned()));
+        o.insert("score".to_owned(), Value::Integer(99));
+
+        Value::Object(o)
+    }
+
+    mod render {
+        use super::*;
+
+        #[test]
+        fn it_renders_inputs() {
+            let res = new_template("{{ inputs[0].name }} {{ inputs[0].score + 1 }}\n").render(&[new_input()], None, false);
+            assert_eq!(res, Ok("Althea 100\n".to_owned()));
+        }
+
+        #[test]
+        fn it_renders_input() {
+            let res = new_template("{{ index }}:{{ input.name }}").render(&[new_input()], Some(2), false);
+            assert_eq!(res, Ok("2:Althea".to_owned()));
+        }
+
+        #[test]
+        fn it_keeps_key_order() {
+            let res = new_template("{% for k, v in inputs[0]|items %}{{ k }};{% endfor %}").render(&[new_input()], None, false);
+            assert_eq!(res, Ok("name;score;".to_owned()));
+        }
+
+        #[test]
+        fn it_fails_in_strict_mode() {
+            let res = new_template("a\n{{ inputs[0].naem }}").render(&[new_input()], None, true);
+            assert!(res.unwrap_err().starts_with("tpl.j2:2: undefined value"));
+        }
+    }
+}
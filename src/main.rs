@@ -1,11 +1,15 @@
 extern crate indexmap;
+extern crate jsonschema;
 extern crate lazy_static;
+extern crate minijinja;
 extern crate regex;
 extern crate rlua;
 extern crate serde;
 extern crate serde_json;
 extern crate serde_yaml;
+extern crate sha2;
 extern crate toml;
+extern crate yaml_rust;
 
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -25,6 +29,7 @@ use crate::util::{
 mod unit;
 use crate::unit::{
     FileFormat,
+    TemplateEngine,
     DocumentHint,
     UnitDocument,
     UnitFile,
@@ -33,9 +38,59 @@ use crate::unit::{
 };
 
 mod value;
-use crate::value::Value;
+use crate::value::{
+    MergeMode,
+    MergeStrategy,
+    Value,
+};
+
+mod canonical;
+use crate::canonical::DigestAlgorithm;
+
+mod check;
+use crate::check::ReportFormat;
+
+mod diff;
+use crate::diff::{
+    DiffFormat,
+    DiffOptions,
+};
+
+mod edit;
+use crate::edit::Edit;
+
+mod flatten;
+use crate::flatten::{
+    ArrayStyle,
+    FlattenOptions,
+};
+
+mod frontmatter;
+use crate::frontmatter::MetaFormat;
+
+mod include;
+
+mod ini;
+use crate::ini::{
+    IniOptions,
+    RepeatedKeys,
+};
+
+mod interpolate;
+use crate::interpolate::InterpolateOptions;
 
 mod command;
+mod dotenv;
+mod hcl;
+mod jinja;
+mod patch;
+mod properties;
+mod ron;
+mod table;
+use crate::table::TableStyle;
+mod query;
+mod reference;
+mod schema;
 mod template;
 mod yaml;
 
@@ -62,17 +117,13 @@ fn read_content(path: &String) -> Result<String, std::io::Error> {
 
         Ok(sb)
     } else {
-        let content =
-                match std::fs::read_to_string(path) {
-                    Ok(c) => c,
-                    Err(e) => return Err(e),
-                };
+        let content = std::fs::read_to_string(path)?;
 
         Ok(content)
     }
 }
 
-fn create_lua_value(content: &str) -> Result<Value, ()> {
+fn create_lua_value(content: &str) -> Result<Value, String> {
     let mut lua_content = String::new();
     lua_content.push_str("ctx:set_output(");
     lua_content.push_str(content);
@@ -81,7 +132,11 @@ fn create_lua_value(content: &str) -> Result<Value, ()> {
     lua.context(|lua_ctx| {
         match lua_ctx.load(command::LUA_PRELUDE).exec() {
             Ok(_) => {},
-            Err(_) => return Err(()),
+            Err(e) => return Err(command::lua_error_message(&e)),
+        }
+        match lua_ctx.load(include::LUA_INCLUDE).exec() {
+            Ok(_) => {},
+            Err(e) => return Err(command::lua_error_message(&e)),
         }
 
         let globals = lua_ctx.globals();
@@ -89,27 +144,159 @@ fn create_lua_value(content: &str) -> Result<Value, ()> {
         let ctx: rlua::Table =
                 match globals.get("ctx") {
                     Ok(v) => v,
-                    Err(_) => return Err(()),
+                    Err(e) => return Err(command::lua_error_message(&e)),
                 };
 
         match lua_ctx.load(&lua_content).exec() {
             Ok(_) => {},
-            Err(_) => return Err(()),
+            Err(e) => return Err(command::lua_error_message(&e)),
         }
 
         let outputs: rlua::Table =
                 match ctx.get("outputs") {
                     Ok(v) => v,
-                    Err(_) => return Err(()),
+                    Err(e) => return Err(command::lua_error_message(&e)),
                 };
 
         match value::from_lua_table(outputs) {
             Value::Array(a) => Ok(a[0].clone()),
-            _ => return Err(()),
+            _ => Err("wrong lua document".to_owned()),
         }
     })
 }
 
+// Reads a document of a given format.
+fn read_document(format: &FileFormat, content: &str) -> Result<Value, String> {
+    match format {
+        FileFormat::Plain => Ok(Value::String(content.to_owned())),
+        FileFormat::Env => dotenv::from_str(content),
+        FileFormat::FrontMatter => frontmatter::from_str(content),
+        FileFormat::Hcl => hcl::from_str(content),
+        FileFormat::HtmlTable | FileFormat::MarkdownTable | FileFormat::Table => Err("output only format".to_owned()),
+        FileFormat::Ini => ini::from_str(content, &IniOptions::default()),
+        FileFormat::Json => value::from_json_str(content),
+        FileFormat::Lua => create_lua_value(content),
+        FileFormat::Properties => properties::from_str(content),
+        FileFormat::Ron => ron::from_str(content),
+        FileFormat::Toml => value::from_toml_str(content),
+        FileFormat::Yaml => value::from_yaml_str(content),
+    }
+}
+
+// Reads the document of an input file, whose includes are expanded when enabled.
+fn parse_input(f: &UnitFile, content: &str) -> Result<Value, String> {
+    let res =
+            if f.format == FileFormat::Ini {
+                // Made of strings and scalars, without include directives.
+                ini::from_str(content, &f.ini.clone().unwrap_or_default())
+            } else if f.has_includes() {
+                include::read_document(&f.format, content, &f.path)
+            } else {
+                read_document(&f.format, content)
+            };
+
+    // The flat keys of env and properties may be nested.
+    match &f.separator {
+        Some(sep) if f.format.is_flat() => res.and_then(|v| flatten::unflatten(&v, &flat_options(sep))),
+        _ => res,
+    }
+}
+
+// Reads the document of an input file, or exits on error.
+fn read_input(f: &UnitFile, content: &str) -> Value {
+    match parse_input(f, content) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}: {}", f.path, e);
+            std::process::exit(21);
+        },
+    }
+}
+
+// Returns the options to nest or flatten the keys of env and properties with a given separator.
+fn flat_options(separator: &str) -> FlattenOptions {
+    FlattenOptions {
+        separator: separator.to_owned(),
+        ..FlattenOptions::default()
+    }
+}
+
+// Reads the variables of the interpolate command from an object-based document of scalars.
+fn read_vars(doc: Value) -> Result<indexmap::IndexMap<String, String>, String> {
+    let entries =
+            match doc {
+                Value::Object(o) => o,
+                _ => return Err("not an object".to_owned()),
+            };
+
+    let mut vars = indexmap::IndexMap::new();
+    for (name, v) in entries.into_iter() {
+        let value =
+                match value::scalar_to_string(&v) {
+                    Some(s) => s,
+                    None => return Err(format!("'{}' is not a scalar", name)),
+                };
+        vars.insert(name, value);
+    } // for
+
+    Ok(vars)
+}
+
+// Checks the inputs preceding the first command, reports their problems and exits.
+fn check_inputs(units: &VecDeque<Unit>, c: &UnitCommand) -> ! {
+    let strict = c.has_strict();
+    let mut reports = Vec::new();
+    for (idx, unit) in units.iter().enumerate() {
+        let (name, problems) =
+                match unit {
+                    Unit::Document(d) => {
+                        let problems =
+                                match create_document(d.hint, &d.content) {
+                                    Ok(_) => Vec::new(),
+                                    Err(_) => vec!["wrong document".to_owned()],
+                                };
+                        (format!("document {}", idx), problems)
+                    },
+                    Unit::File(f) => {
+                        let problems =
+                                match read_content(&f.path) {
+                                    // Read like the inputs of the other commands, with their options.
+                                    Ok(content) if f.format == FileFormat::Yaml && f.has_stream() => check::check_stream(&content, strict, &|c| parse_input(f, c)),
+                                    Ok(content) => check::check_document(&f.format, &content, strict, &|c| parse_input(f, c)),
+                                    Err(e) => vec![e.to_string()],
+                                };
+                        (f.path.clone(), problems)
+                    },
+                    _ => break,
+                };
+        reports.push((name, problems));
+    } // for
+
+    let has_problems = reports.iter().any(|(_, p)| !p.is_empty());
+    match c.report.unwrap_or_default() {
+        ReportFormat::Text => {
+            for (name, problems) in reports.iter() {
+                for problem in problems.iter() {
+                    eprintln!("{}: {}", name, problem);
+                } // for
+            } // for
+        },
+        ReportFormat::Json => {
+            let entries = reports.into_iter()
+                .map(|(name, problems)| {
+                    let mut entry = indexmap::IndexMap::new();
+                    entry.insert("input".to_owned(), Value::String(name));
+                    entry.insert("problems".to_owned(), Value::Array(problems.into_iter().map(Value::String).collect()));
+                    Value::Object(entry)
+                })
+                .collect();
+            println!("{}", serde_json::to_string(&Value::Array(entries)).unwrap());
+        },
+    }
+
+    std::process::exit(if has_problems { 21 } else { 0 });
+}
+
 // Creates a document.
 fn create_document(hint: DocumentHint, content: &str) -> Result<Value, ()> {
     match hint {
@@ -200,6 +387,211 @@ fn create_document(hint: DocumentHint, content: &str) -> Result<Value, ()> {
     }
 }
 
+// Renders a template with given input documents.
+// The index is the one of the document when rendering once per document.
+fn render_template(template: &template::Template, inputs: &[Value], index: Option<usize>, strict: bool) -> Option<String> {
+    let lua = rlua::Lua::new();
+    lua.context(|lua_ctx| {
+        match lua_ctx.load(command::LUA_PRELUDE).exec() {
+            Ok(_) => {},
+            Err(e) => panic!("{}", e),
+        }
+
+        let globals = lua_ctx.globals();
+
+        let ctx: rlua::Table =
+                match globals.get("ctx") {
+                    Ok(v) => v,
+                    Err(e) => panic!("{}", e),
+                };
+
+        for value in inputs.iter() {
+            let mut sb = String::new();
+            sb.push_str("table.insert(ctx.inputs,");
+            sb.push_str(&value::to_lua_string(value));
+            sb.push(')');
+            lua_ctx.load(&sb).exec().unwrap();
+        } // for
+
+        if let Some(idx) = index {
+            let mut sb = String::new();
+            sb.push_str("ctx.input = ctx.inputs[1]\n");
+            sb.push_str(&format!("ctx.index = {}\n", idx));
+            lua_ctx.load(&sb).exec().unwrap();
+        }
+
+        if strict {
+            match lua_ctx.load(command::LUA_STRICT).exec() {
+                Ok(_) => {},
+                Err(e) => panic!("{}", e),
+            }
+        }
+
+        let chunk =
+                match lua_ctx.load(&template.content).set_name(&template.chunk_name()) {
+                    Ok(c) => c,
+                    Err(e) => panic!("{}", e),
+                };
+        match chunk.exec() {
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("{}", template.error_message(&e));
+                std::process::exit(21);
+            },
+        }
+
+        let outputs: rlua::Table =
+                match ctx.get("outputs") {
+                    Ok(v) => v,
+                    Err(e) => panic!("{}", e),
+                };
+
+        match outputs.get(1).unwrap() {
+            rlua::Value::Table(t) => Some(value::from_processed_template(t)),
+            _ => None,
+        }
+    })
+}
+
+// Renders all given documents at once, or once per document.
+fn render_all<F>(inputs: &[Value], each: bool, mut render: F) -> Vec<String>
+where
+    F: FnMut(&[Value], Option<usize>) -> Option<String>,
+{
+    let mut outputs = Vec::new();
+    if each {
+        for (idx, value) in inputs.iter().enumerate() {
+            if let Some(v) = render(std::slice::from_ref(value), Some(idx + 1)) {
+                outputs.push(v);
+            }
+        } // for
+    } else if let Some(v) = render(inputs, None) {
+        outputs.push(v);
+    }
+
+    outputs
+}
+
+// Reads the text rendered by the template of a given path as a document of a given format.
+fn read_rendered_document(path: &str, format: &FileFormat, content: &str) -> Value {
+    match read_document(format, content) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}: wrong rendered document: {}", path, e);
+            lazy_static! {
+                static ref LINE_RE: Regex = Regex::new("line ([0-9]+)").unwrap();
+            }
+            // Show the offending line of the rendered text.
+            if let Some(caps) = LINE_RE.captures(&e) {
+                let line = caps[1].parse::<usize>().unwrap_or(0);
+                if let Some(l) = content.lines().nth(line.wrapping_sub(1)) {
+                    eprintln!("{:>6} | {}", line, l);
+                }
+            }
+            std::process::exit(21);
+        },
+    }
+}
+
+// Pops an option given either as '--name VALUE' or as '--name=VALUE', and returns its value.
+// The description names the value in error messages.
+fn pop_option_value(args: &mut VecDeque<String>, desc: &str) -> String {
+    let opt = args.pop_front().unwrap();
+    match opt.split_once('=') {
+        Some((_, v)) => v.to_owned(),
+        None => match args.pop_front() {
+            Some(v) => v,
+            None => {
+                eprintln!("missing {}", desc);
+                std::process::exit(10);
+            },
+        },
+    }
+}
+
+// Parses the argument of a given edit command:
+// - 'PATH[:HINT]=VALUE' for set, where the value is typed like a document,
+// - 'PATH' for delete,
+// - 'OLD=NEW' for rename.
+fn parse_edit(name: &str, spec: &str) -> Result<Edit, String> {
+    match name {
+        "set" => {
+            let (target, content) =
+                    match spec.split_once('=') {
+                        Some(s) => s,
+                        None => return Err(format!("missing value in '{}'", spec)),
+                    };
+            let (path, hint) =
+                    match target.rsplit_once(':') {
+                        Some((p, h)) => match DocumentHint::for_str(h) {
+                            Ok(hint) => (p, hint),
+                            Err(_) => (target, DocumentHint::Any),
+                        },
+                        None => (target, DocumentHint::Any),
+                    };
+            let tokens = edit::parse_path(path)?;
+            match create_document(hint, content) {
+                Ok(v) => Ok(Edit::Set(tokens, v)),
+                Err(_) => Err(format!("wrong value '{}'", content)),
+            }
+        },
+        "delete" => Ok(Edit::Delete(edit::parse_path(spec)?)),
+        _ => match spec.split_once('=') {
+            Some((old, new)) => Ok(Edit::Rename(edit::parse_path(old)?, edit::parse_path(new)?)),
+            None => Err(format!("missing new path in '{}'", spec)),
+        },
+    }
+}
+
+// Parses the options and the mandatory path of a Render or a Transform command.
+// The kind names the path in error messages, either "template" or "lua".
+fn parse_script_command(args: &mut VecDeque<String>, kind: &str) -> UnitCommand {
+    let mut ucmd = UnitCommand::default();
+    loop {
+        let next_opt =
+                match args.front() {
+                    Some(o) => o,
+                    None => {
+                        eprintln!("missing {} path", kind);
+                        std::process::exit(10);
+                    },
+                };
+        if next_opt == "--strict" {
+            args.pop_front();
+            ucmd.strict = Some(true);
+        } else if kind == "template" && (next_opt == "--as" || next_opt.starts_with("--as=")) {
+            let format = pop_option_value(args, "format");
+            match FileFormat::for_str(&format) {
+                Ok(f) => ucmd.format = Some(f),
+                Err(_) => {
+                    eprintln!("unknown file format");
+                    std::process::exit(11);
+                },
+            }
+        } else if kind == "template" && (next_opt == "--engine" || next_opt.starts_with("--engine=")) {
+            let engine = pop_option_value(args, "template engine");
+            match TemplateEngine::for_str(&engine) {
+                Ok(e) => ucmd.engine = Some(e),
+                Err(_) => {
+                    eprintln!("wrong template engine");
+                    std::process::exit(10);
+                },
+            }
+        } else {
+            let path = args.pop_front().unwrap();
+            if path != STDIO_PLACEHOLDER && path.starts_with(STDIO_PLACEHOLDER) {
+                eprintln!("wrong {} path", kind);
+                std::process::exit(10);
+            }
+
+            ucmd.path = Some(path);
+            break;
+        }
+    } // loop
+
+    ucmd
+}
+
 /**
  * Exit codes:
  * - INTERNAL_ERROR(1)
@@ -209,6 +601,7 @@ fn create_document(hint: DocumentHint, content: &str) -> Result<Value, ()> {
  * - WRONG_INPUT(21)
  * - NO_OUTPUT(30)
  * - WRONG_OUTPUT(31)
+ * - DIFFERENT_DOCUMENTS(40)
  */
 fn main() {
     // (1ofx) Parse arguments.
@@ -233,31 +626,46 @@ fn main() {
                 _ => {},
             }
         },
-        2 => {
-            match args.front().unwrap().as_str() {
-                HELP_CMD => {
-                    args.pop_front();
-                    match args.pop_front().unwrap().as_str() {
-                        "check" => println!("{}", help::CHECK_HELP),
-                        "concat" => println!("{}", help::CONCAT_HELP),
-                        "copy" => println!("{}", help::COPY_HELP),
-                        "document" => println!("{}", help::DOCUMENT_HELP),
-                        "json" => println!("{}", help::JSON_HELP),
-                        "lua" => println!("{}", help::LUA_HELP),
-                        "merge" => println!("{}", help::MERGE_HELP),
-                        "pack" => println!("{}", help::PACK_HELP),
-                        "plain" => println!("{}", help::PLAIN_HELP),
-                        "render" => println!("{}", help::RENDER_HELP),
-                        "toml" => println!("{}", help::TOML_HELP),
-                        "transform" => println!("{}", help::TRANSFORM_HELP),
-                        "unpack" => println!("{}", help::UNPACK_HELP),
-                        "yaml" => println!("{}", help::YAML_HELP),
-                        _ => println!("{}", help::TOPIC_HELP),
-                    }
-                    std::process::exit(0);
-                },
-                _ => {},
+        2 if args.front().unwrap() == HELP_CMD => {
+            args.pop_front();
+            match args.pop_front().unwrap().as_str() {
+                "canonicalize" => println!("{}", help::CANONICALIZE_HELP),
+                "check" => println!("{}", help::CHECK_HELP),
+                "compile-template" => println!("{}", help::COMPILE_TEMPLATE_HELP),
+                "concat" => println!("{}", help::CONCAT_HELP),
+                "copy" => println!("{}", help::COPY_HELP),
+                "delete" | "rename" | "set" => println!("{}", help::EDIT_HELP),
+                "diff" => println!("{}", help::DIFF_HELP),
+                "digest" => println!("{}", help::DIGEST_HELP),
+                "document" => println!("{}", help::DOCUMENT_HELP),
+                "env" => println!("{}", help::ENV_HELP),
+                "flatten" | "unflatten" => println!("{}", help::FLATTEN_HELP),
+                "frontmatter" => println!("{}", help::FRONTMATTER_HELP),
+                "hcl" => println!("{}", help::HCL_HELP),
+                "infer-schema" => println!("{}", help::INFER_SCHEMA_HELP),
+                "ini" => println!("{}", help::INI_HELP),
+                "interpolate" => println!("{}", help::INTERPOLATE_HELP),
+                "json" => println!("{}", help::JSON_HELP),
+                "json-patch" => println!("{}", help::JSON_PATCH_HELP),
+                "lua" => println!("{}", help::LUA_HELP),
+                "merge" | "merge-patch-diff" => println!("{}", help::MERGE_HELP),
+                "pack" => println!("{}", help::PACK_HELP),
+                "plain" => println!("{}", help::PLAIN_HELP),
+                "properties" => println!("{}", help::PROPERTIES_HELP),
+                "query" | "query-each" => println!("{}", help::QUERY_HELP),
+                "ron" => println!("{}", help::RON_HELP),
+                "table" | "html-table" | "markdown-table" => println!("{}", help::TABLE_HELP),
+                "render" => println!("{}", help::RENDER_HELP),
+                "render-each" => println!("{}", help::RENDER_EACH_HELP),
+                "resolve-refs" => println!("{}", help::RESOLVE_REFS_HELP),
+                "toml" => println!("{}", help::TOML_HELP),
+                "transform" => println!("{}", help::TRANSFORM_HELP),
+                "unpack" => println!("{}", help::UNPACK_HELP),
+                "validate" => println!("{}", help::VALIDATE_HELP),
+                "yaml" => println!("{}", help::YAML_HELP),
+                _ => println!("{}", help::TOPIC_HELP),
             }
+            std::process::exit(0);
         },
         _ => {},
     }
@@ -296,20 +704,177 @@ fn main() {
                             };
                     units.push_back(Unit::Document(doc));
                 } else if option == "check" {
-                    units.push_back(Unit::Check);
+                    // With optional strict mode and report format.
+                    let mut ucmd = UnitCommand::default();
+                    while let Some(next_opt) = args.front() {
+                        if next_opt == "--strict" {
+                            args.pop_front();
+                            ucmd.strict = Some(true);
+                        } else if next_opt == "--report" || next_opt.starts_with("--report=") {
+                            let format = pop_option_value(&mut args, "report format");
+                            match ReportFormat::for_str(&format) {
+                                Ok(f) => ucmd.report = Some(f),
+                                Err(_) => {
+                                    eprintln!("wrong report format");
+                                    std::process::exit(10);
+                                },
+                            }
+                        } else {
+                            break;
+                        }
+                    } // while
+                    units.push_back(Unit::Check(ucmd));
+                } else if option == "canonicalize" {
+                    units.push_back(Unit::Canonicalize);
                 } else if option == "copy" {
                     units.push_back(Unit::Copy);
                 } else if option == "concat" {
                     units.push_back(Unit::Concat);
+                } else if option == "resolve-refs" {
+                    units.push_back(Unit::ResolveRefs);
+                } else if option == "flatten" || option == "unflatten" {
+                    // With optional separator and array style.
+                    let mut options = FlattenOptions::default();
+                    while let Some(next_opt) = args.front() {
+                        if next_opt == "--separator" || next_opt.starts_with("--separator=") {
+                            let separator = pop_option_value(&mut args, "separator");
+                            if separator.is_empty() {
+                                eprintln!("wrong separator");
+                                std::process::exit(10);
+                            }
+                            options.separator = separator;
+                        } else if next_opt == "--array-style" || next_opt.starts_with("--array-style=") {
+                            let style = pop_option_value(&mut args, "array style");
+                            match ArrayStyle::for_str(&style) {
+                                Ok(s) => options.array_style = s,
+                                Err(_) => {
+                                    eprintln!("wrong array style");
+                                    std::process::exit(10);
+                                },
+                            }
+                        } else {
+                            break;
+                        }
+                    } // while
+                    let ucmd = UnitCommand {
+                        flatten: Some(options),
+                        ..UnitCommand::default()
+                    };
+                    if option == "flatten" {
+                        units.push_back(Unit::Flatten(ucmd));
+                    } else {
+                        units.push_back(Unit::Unflatten(ucmd));
+                    }
+                } else if option == "infer-schema" {
+                    units.push_back(Unit::InferSchema);
+                } else if option == "interpolate" {
+                    // With optional vars document, strict mode and retyping.
+                    let mut ucmd = UnitCommand::default();
+                    while let Some(next_opt) = args.front() {
+                        if next_opt == "--vars" || next_opt.starts_with("--vars=") {
+                            ucmd.path = Some(pop_option_value(&mut args, "vars path"));
+                        } else if next_opt == "--strict" {
+                            args.pop_front();
+                            ucmd.strict = Some(true);
+                        } else if next_opt == "--retype" {
+                            args.pop_front();
+                            ucmd.retype = Some(true);
+                        } else {
+                            break;
+                        }
+                    } // while
+                    units.push_back(Unit::Interpolate(ucmd));
+                } else if option == "validate" {
+                    // With optional filling of default values.
+                    let mut ucmd = UnitCommand::default();
+                    if args.front().map(|a| a.as_str()) == Some("--fill-defaults") {
+                        args.pop_front();
+                        ucmd.defaults = Some(true);
+                    }
+                    match args.pop_front() {
+                        Some(p) => ucmd.path = Some(p),
+                        None => {
+                            eprintln!("missing schema path");
+                            std::process::exit(10);
+                        },
+                    }
+                    units.push_back(Unit::Validate(ucmd));
+                } else if option == "set" || option == "delete" || option == "rename" {
+                    let spec =
+                            match args.pop_front() {
+                                Some(s) => s,
+                                None => {
+                                    eprintln!("missing path");
+                                    std::process::exit(10);
+                                },
+                            };
+                    let edit =
+                            match parse_edit(option, &spec) {
+                                Ok(e) => e,
+                                Err(e) => {
+                                    eprintln!("{}: {}", option, e);
+                                    std::process::exit(10);
+                                },
+                            };
+                    let ucmd = UnitCommand {
+                        edit: Some(edit),
+                        ..UnitCommand::default()
+                    };
+                    units.push_back(Unit::Edit(ucmd));
+                } else if option == "diff" {
+                    // With optional format and order options.
+                    let mut options = DiffOptions::default();
+                    while let Some(next_opt) = args.front() {
+                        if next_opt == "--format" || next_opt.starts_with("--format=") {
+                            let format = pop_option_value(&mut args, "diff format");
+                            match DiffFormat::for_str(&format) {
+                                Ok(f) => options.format = f,
+                                Err(_) => {
+                                    eprintln!("wrong diff format");
+                                    std::process::exit(10);
+                                },
+                            }
+                        } else if next_opt == "--ignore-key-order" {
+                            args.pop_front();
+                            options.ignore_key_order = true;
+                        } else if next_opt == "--ignore-array-order" {
+                            args.pop_front();
+                            options.ignore_array_order = true;
+                        } else {
+                            break;
+                        }
+                    } // while
+                    let ucmd = UnitCommand {
+                        diff: Some(options),
+                        ..UnitCommand::default()
+                    };
+                    units.push_back(Unit::Diff(ucmd));
+                } else if option == "digest" {
+                    // With mandatory algorithm.
+                    let algorithm =
+                            match args.pop_front() {
+                                Some(a) => a,
+                                None => {
+                                    eprintln!("missing digest algorithm");
+                                    std::process::exit(10);
+                                },
+                            };
+                    let ucmd =
+                            match DigestAlgorithm::for_str(&algorithm) {
+                                Ok(a) => UnitCommand {
+                                    digest: Some(a),
+                                    ..UnitCommand::default()
+                                },
+                                Err(_) => {
+                                    eprintln!("wrong digest algorithm");
+                                    std::process::exit(10);
+                                },
+                            };
+                    units.push_back(Unit::Digest(ucmd));
                 } else if option == "merge" {
-                    // With optional depth.
+                    // With optional depth and strategy, or as merge patches.
                     let mut ucmd = UnitCommand::default();
-                    loop {
-                        let next_opt =
-                                match args.front() {
-                                    Some(o) => o,
-                                    None => break,
-                                };
+                    while let Some(next_opt) = args.front() {
                         if next_opt.starts_with("--depth") || next_opt.starts_with("-d") {
                             let opt =
                                     match FlaggedOption::from_str(&args.pop_front().unwrap()) {
@@ -334,48 +899,111 @@ fn main() {
                                     ucmd.depth = Some(depth);
                                 },
                             }
+                        } else if next_opt == "--arrays" || next_opt.starts_with("--arrays=") {
+                            let mode = pop_option_value(&mut args, "merge mode");
+                            let mode =
+                                    match MergeMode::for_str(&mode) {
+                                        Ok(m) => m,
+                                        Err(_) => {
+                                            eprintln!("wrong merge mode");
+                                            std::process::exit(10);
+                                        },
+                                    };
+                            ucmd.strategy.get_or_insert_with(MergeStrategy::default).arrays = mode;
+                        } else if next_opt == "--strategy" || next_opt.starts_with("--strategy=") {
+                            let strategy = pop_option_value(&mut args, "merge strategy");
+                            let (pointer, mode) =
+                                    match strategy.rsplit_once('=') {
+                                        Some(s) => s,
+                                        None => {
+                                            eprintln!("wrong merge strategy");
+                                            std::process::exit(10);
+                                        },
+                                    };
+                            let tokens =
+                                    match value::pointer_tokens(pointer) {
+                                        Ok(t) => t,
+                                        Err(_) => {
+                                            eprintln!("wrong merge strategy path");
+                                            std::process::exit(10);
+                                        },
+                                    };
+                            let mode =
+                                    match MergeMode::for_str(mode) {
+                                        Ok(m) => m,
+                                        Err(_) => {
+                                            eprintln!("wrong merge mode");
+                                            std::process::exit(10);
+                                        },
+                                    };
+                            ucmd.strategy.get_or_insert_with(MergeStrategy::default).paths.push((tokens, mode));
+                        } else if next_opt == "--patch" {
+                            args.pop_front();
+                            ucmd.patch = Some(true);
                         } else {
                             break;
                         }
-                    } // loop
+                    } // while
+                    if ucmd.has_patch() && (ucmd.depth.is_some() || ucmd.strategy.is_some()) {
+                        eprintln!("cannot merge patches with a depth or a strategy");
+                        std::process::exit(10);
+                    }
                     units.push_back(Unit::Merge(ucmd));
+                } else if option == "merge-patch-diff" {
+                    units.push_back(Unit::MergePatchDiff);
                 } else if option == "pack" {
                     units.push_back(Unit::Pack);
+                } else if option == "json-patch" {
+                    units.push_back(Unit::JsonPatch);
                 } else if option == "unpack" {
                     units.push_back(Unit::Unpack);
-                } else if option == "render" {
-                    // With mandatory path.
-                    let path =
+                } else if option == "query" || option == "query-each" {
+                    let expr =
                             match args.pop_front() {
-                                Some(p) => p,
+                                Some(e) => e,
                                 None => {
-                                    eprintln!("missing template path");
+                                    eprintln!("missing query");
                                     std::process::exit(10);
                                 },
                             };
-                    if path != STDIO_PLACEHOLDER && path.starts_with(STDIO_PLACEHOLDER) {
-                        eprintln!("wrong template path");
-                        std::process::exit(10);
-                    }
-
-                    let ucmd = UnitCommand::for_path(&path);
-                    units.push_back(Unit::Render(ucmd));
-                } else if option == "transform" {
-                    // With mandatory path.
-                    let path =
-                            match args.pop_front() {
-                                Some(p) => p,
-                                None => {
-                                    eprintln!("missing lua path");
+                    let query =
+                            match query::Query::parse(&expr) {
+                                Ok(q) => q,
+                                Err(e) => {
+                                    eprintln!("wrong query: {}", e);
                                     std::process::exit(10);
                                 },
                             };
-                    if path != STDIO_PLACEHOLDER && path.starts_with(STDIO_PLACEHOLDER) {
-                        eprintln!("wrong lua path");
+                    let ucmd = UnitCommand {
+                        query: Some(query),
+                        each: Some(option == "query-each"),
+                        ..UnitCommand::default()
+                    };
+                    units.push_back(Unit::Query(ucmd));
+                } else if option == "render" {
+                    // With optional strict mode and mandatory path.
+                    let ucmd = parse_script_command(&mut args, "template");
+                    units.push_back(Unit::Render(ucmd));
+                } else if option == "compile-template" {
+                    // With mandatory path.
+                    let ucmd = parse_script_command(&mut args, "template");
+                    if ucmd.has_strict() || ucmd.format.is_some() || ucmd.engine.is_some() {
+                        eprintln!("wrong parameter");
                         std::process::exit(10);
                     }
-
-                    let ucmd = UnitCommand::for_path(&path);
+                    if TemplateEngine::for_path(ucmd.path.as_ref().unwrap()) != TemplateEngine::Lua {
+                        eprintln!("wrong template engine");
+                        std::process::exit(10);
+                    }
+                    units.push_back(Unit::CompileTemplate(ucmd));
+                } else if option == "render-each" {
+                    // With optional strict mode and mandatory path.
+                    let mut ucmd = parse_script_command(&mut args, "template");
+                    ucmd.each = Some(true);
+                    units.push_back(Unit::Render(ucmd));
+                } else if option == "transform" {
+                    // With optional strict mode and mandatory path.
+                    let ucmd = parse_script_command(&mut args, "lua");
                     units.push_back(Unit::Transform(ucmd));
                 } else {
                     // A file format -> input or output.
@@ -404,12 +1032,59 @@ fn main() {
                         } else if next_opt == "--eol" {
                             args.pop_front();
                             ufile.eol = Some(true);
+                        } else if next_opt == "--include" {
+                            args.pop_front();
+                            ufile.includes = Some(true);
                         } else if next_opt == "--fix" {
                             args.pop_front();
                             ufile.fix = Some(true);
                         } else if next_opt == "--pretty" {
                             args.pop_front();
                             ufile.pretty = Some(true);
+                        } else if next_opt == "--canonical" {
+                            args.pop_front();
+                            ufile.canonical = Some(true);
+                        } else if next_opt == "--columns" || next_opt.starts_with("--columns=") {
+                            let columns: Vec<String> = pop_option_value(&mut args, "columns").split(',').map(|c| c.trim().to_owned()).collect();
+                            if columns.iter().any(String::is_empty) {
+                                eprintln!("wrong columns");
+                                std::process::exit(10);
+                            }
+                            ufile.columns = Some(columns);
+                        } else if next_opt == "--meta" || next_opt.starts_with("--meta=") {
+                            let format = pop_option_value(&mut args, "meta format");
+                            match MetaFormat::for_str(&format) {
+                                Ok(f) => ufile.meta = Some(f),
+                                Err(_) => {
+                                    eprintln!("wrong meta format");
+                                    std::process::exit(10);
+                                },
+                            }
+                        } else if next_opt == "--blocks" {
+                            args.pop_front();
+                            ufile.blocks = Some(true);
+                        } else if next_opt == "--separator" || next_opt.starts_with("--separator=") {
+                            let separator = pop_option_value(&mut args, "separator");
+                            if separator.is_empty() {
+                                eprintln!("wrong separator");
+                                std::process::exit(10);
+                            }
+                            ufile.separator = Some(separator);
+                        } else if next_opt == "--typed" {
+                            args.pop_front();
+                            ufile.ini.get_or_insert_with(IniOptions::default).typed = true;
+                        } else if next_opt == "--comments" || next_opt.starts_with("--comments=") {
+                            let comments = pop_option_value(&mut args, "comment characters");
+                            ufile.ini.get_or_insert_with(IniOptions::default).comments = comments;
+                        } else if next_opt == "--repeated-keys" || next_opt.starts_with("--repeated-keys=") {
+                            let mode = pop_option_value(&mut args, "repeated keys mode");
+                            match RepeatedKeys::for_str(&mode) {
+                                Ok(m) => ufile.ini.get_or_insert_with(IniOptions::default).repeated_keys = m,
+                                Err(_) => {
+                                    eprintln!("wrong repeated keys mode");
+                                    std::process::exit(10);
+                                },
+                            }
                         } else if next_opt.starts_with("--stream") || next_opt.starts_with("-s") {
                             let opt =
                                     match FlaggedOption::from_str(&args.pop_front().unwrap()) {
@@ -500,38 +1175,12 @@ fn main() {
                     units.push_back(Unit::Document(doc));
                 }
             } else if option == "R" {
-                // With mandatory path.
-                let path =
-                        match args.pop_front() {
-                            Some(p) => p,
-                            None => {
-                                eprintln!("missing template path");
-                                std::process::exit(10);
-                            },
-                        };
-                if path != STDIO_PLACEHOLDER && path.starts_with(STDIO_PLACEHOLDER) {
-                    eprintln!("wrong template path");
-                    std::process::exit(10);
-                }
-
-                let ucmd = UnitCommand::for_path(&path);
+                // With optional strict mode and mandatory path.
+                let ucmd = parse_script_command(&mut args, "template");
                 units.push_back(Unit::Render(ucmd));
             } else if option == "T" {
-                // With mandatory path.
-                let path =
-                        match args.pop_front() {
-                            Some(p) => p,
-                            None => {
-                                eprintln!("missing lua path");
-                                std::process::exit(10);
-                            },
-                        };
-                if path != STDIO_PLACEHOLDER && path.starts_with(STDIO_PLACEHOLDER) {
-                    eprintln!("wrong lua path");
-                    std::process::exit(10);
-                }
-
-                let ucmd = UnitCommand::for_path(&path);
+                // With optional strict mode and mandatory path.
+                let ucmd = parse_script_command(&mut args, "lua");
                 units.push_back(Unit::Transform(ucmd));
             } else {
                 eprintln!("wrong parameter");
@@ -551,13 +1200,20 @@ fn main() {
     } // for
 
     // (2ofx) Read input documents.
+    // The check command reads the inputs by itself, to report every problem instead of the first one.
+    // After another command, the inputs have been read once it is reached, and it can't be strict.
+    let mut commands = units.iter().filter(|u| !matches!(u, Unit::Document(_) | Unit::File(_)));
+    if let Some(Unit::Check(c)) = commands.next() {
+        check_inputs(&units, c);
+    }
+    if commands.any(|u| matches!(u, Unit::Check(c) if c.strict.is_some() || c.report.is_some())) {
+        eprintln!("check options require check to be the first command");
+        std::process::exit(10);
+    }
     let mut values = VecDeque::new();
-    loop {
-        let unit =
-                match units.pop_front() {
-                    Some(u) => u,
-                    None => break,
-                };
+    // The format of the first front matter read, which is the default of the written ones.
+    let mut meta_format = None;
+    while let Some(unit) = units.pop_front() {
         match unit {
             Unit::Document(d) => {
                 let value =
@@ -571,81 +1227,42 @@ fn main() {
 
                 values.push_back(value);
             },
-            Unit::File(f) => match f.format {
-                FileFormat::Plain => {
-                    let content =
-                            match read_content(&f.path) {
-                                Ok(c) => c,
-                                Err(e) => panic!("{}", e),
-                            };
-                    values.push_back(Value::String(content));
-                },
-                FileFormat::Json => {
-                    let content =
-                            match read_content(&f.path) {
-                                Ok(c) => c,
-                                Err(e) => panic!("{}", e),
-                            };
-                    values.push_back(value::from_json_str(&content).unwrap());
-                },
-                FileFormat::Lua => {
-                    let content =
-                            match read_content(&f.path) {
-                                Ok(c) => c,
-                                Err(e) => panic!("{}", e),
-                            };
-                    values.push_back(create_lua_value(&content).unwrap());
-                },
-                FileFormat::Toml => {
-                    let content =
-                            match read_content(&f.path) {
+            Unit::File(f) => {
+                let content =
+                        match read_content(&f.path) {
+                            Ok(c) => c,
+                            Err(e) => panic!("{}", e),
+                        };
+                if f.format == FileFormat::FrontMatter && meta_format.is_none() {
+                    meta_format = frontmatter::meta_format(&content);
+                }
+                if f.format == FileFormat::Yaml && f.has_stream() {
+                    let docs =
+                            match yaml::read_stream(&content) {
                                 Ok(c) => c,
-                                Err(e) => panic!("{}", e),
+                                Err(_) => {
+                                    eprintln!("wrong input");
+                                    std::process::exit(21);
+                                },
                             };
-                    values.push_back(value::from_toml_str(&content).unwrap());
-                },
-                FileFormat::Yaml => {
-                    if f.has_stream() {
-                        let content =
-                                match read_content(&f.path) {
-                                    Ok(c) => c,
-                                    Err(e) => panic!("{}", e),
-                                };
-                        let docs =
-                                match yaml::read_stream(&content) {
-                                    Ok(c) => c,
-                                    Err(_) => {
-                                        eprintln!("wrong input");
-                                        std::process::exit(21);
-                                    },
-                                };
-                        for doc in docs.iter() {
-                            values.push_back(value::from_yaml_str(&doc).unwrap());
-                        } // for
-                    } else {
-                        let content =
-                                match read_content(&f.path) {
-                                    Ok(c) => c,
-                                    Err(e) => panic!("{}", e),
-                                };
-                        values.push_back(value::from_yaml_str(&content).unwrap());
-                    }
-                },
+                    for doc in docs.iter() {
+                        values.push_back(read_input(&f, doc));
+                    } // for
+                } else {
+                    values.push_back(read_input(&f, &content));
+                }
             },
             _ => {
                 units.push_front(unit);
                 break;
             },
         }
-    } // loop
+    } // while
 
     // (3ofx) Process commands.
-    loop {
-        let unit =
-                match units.pop_front() {
-                    Some(u) => u,
-                    None => break,
-                };
+    // Set when the diff command finds differences, to exit with a non-zero code once the outputs are written.
+    let mut has_differences = false;
+    while let Some(unit) = units.pop_front() {
         match unit {
             Unit::Copy => {
                 // No treatment necessary since every input will be written afterwards.
@@ -674,16 +1291,14 @@ fn main() {
 
                 values.push_back(Value::Array(res));
             },
-            Unit::Check => {
-                // Nothing else to do since every input has been read and checked already.
+            Unit::Check(_) => {
+                // Nothing else to do since every input has been read and the previous commands succeeded.
                 std::process::exit(0);
             },
             Unit::Merge(c) => {
-                let depth =
-                        match c.depth {
-                            Some(d) => d,
-                            None => -1,
-                        };
+                let depth = c.depth.unwrap_or(-1);
+                let patch = c.has_patch();
+                let strategy = c.strategy.unwrap_or_default();
                 loop {
                     match values.len() {
                         0 => panic!("cannot merge without any input"),
@@ -693,10 +1308,232 @@ fn main() {
 
                     let left = values.pop_front().unwrap();
                     let right = values.pop_front().unwrap();
-                    let res = value::merge_values(&left, &right, depth);
+                    let res =
+                            if patch {
+                                value::merge_patch(&left, &right)
+                            } else {
+                                value::merge_values(&left, &right, depth, &strategy)
+                            };
                     values.push_front(res);
                 } // loop
             },
+            Unit::MergePatchDiff => {
+                if values.len() != 2 {
+                    eprintln!("merge-patch-diff requires 2 documents");
+                    std::process::exit(21);
+                }
+
+                let left = values.pop_front().unwrap();
+                let right = values.pop_front().unwrap();
+                match value::merge_patch_diff(&left, &right) {
+                    Ok(v) => values.push_back(v),
+                    Err(e) => {
+                        eprintln!("merge-patch-diff: {}", e);
+                        std::process::exit(21);
+                    },
+                }
+            },
+            Unit::Diff(c) => {
+                if values.len() < 2 {
+                    eprintln!("diff requires 2 documents");
+                    std::process::exit(21);
+                }
+
+                let options = c.diff.unwrap_or_default();
+                let left = values.pop_front().unwrap();
+                let right = values.pop_front().unwrap();
+                let changes = diff::diff_values(&left, &right, &options);
+                has_differences = !changes.is_empty();
+                match diff::format_diff(&left, &right, &changes, &options) {
+                    Ok(v) => values.push_front(v),
+                    Err(e) => {
+                        eprintln!("diff: {}", e);
+                        std::process::exit(21);
+                    },
+                }
+            },
+            Unit::Query(c) => {
+                let doc =
+                        match values.pop_front() {
+                            Some(v) => v,
+                            None => {
+                                eprintln!("query requires a document");
+                                std::process::exit(21);
+                            },
+                        };
+                let matches: Vec<Value> = c.query.as_ref().unwrap().select(&doc).into_iter().cloned().collect();
+                if c.has_each() {
+                    for m in matches.into_iter().rev() {
+                        values.push_front(m);
+                    } // for
+                } else {
+                    values.push_front(Value::Array(matches));
+                }
+            },
+            Unit::Edit(c) => {
+                let edit = c.edit.unwrap();
+                let doc =
+                        match values.pop_front() {
+                            Some(v) => v,
+                            None => {
+                                eprintln!("{} requires a document", edit.name());
+                                std::process::exit(21);
+                            },
+                        };
+                match edit::apply_edit(&doc, &edit) {
+                    Ok(v) => values.push_front(v),
+                    Err(e) => {
+                        eprintln!("{}: {}", edit.name(), e);
+                        std::process::exit(21);
+                    },
+                }
+            },
+            Unit::Canonicalize => {
+                for val in values.iter_mut() {
+                    *val = canonical::sort_keys(val);
+                } // for
+            },
+            Unit::Digest(c) => {
+                let algorithm = c.digest.unwrap();
+                for (idx, val) in values.iter_mut().enumerate() {
+                    match canonical::digest(val, algorithm) {
+                        Ok(d) => *val = Value::String(d),
+                        Err(e) => {
+                            eprintln!("document {}: {}", idx, e);
+                            std::process::exit(21);
+                        },
+                    }
+                } // for
+            },
+            Unit::Flatten(c) => {
+                let options = c.flatten.unwrap_or_default();
+                for (idx, val) in values.iter_mut().enumerate() {
+                    match flatten::flatten(val, &options) {
+                        Ok(v) => *val = v,
+                        Err(e) => {
+                            eprintln!("document {}: {}", idx, e);
+                            std::process::exit(21);
+                        },
+                    }
+                } // for
+            },
+            Unit::Unflatten(c) => {
+                let options = c.flatten.unwrap_or_default();
+                for (idx, val) in values.iter_mut().enumerate() {
+                    match flatten::unflatten(val, &options) {
+                        Ok(v) => *val = v,
+                        Err(e) => {
+                            eprintln!("document {}: {}", idx, e);
+                            std::process::exit(21);
+                        },
+                    }
+                } // for
+            },
+            Unit::ResolveRefs => {
+                let docs: Vec<Value> = values.drain(..).collect();
+                match reference::resolve_refs(&docs) {
+                    Ok(res) => values.extend(res),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(21);
+                    },
+                }
+            },
+            Unit::InferSchema => {
+                if values.is_empty() {
+                    eprintln!("infer-schema requires a document");
+                    std::process::exit(21);
+                }
+
+                let samples: Vec<Value> = values.drain(..).collect();
+                values.push_back(schema::infer_schema(&samples));
+            },
+            Unit::Interpolate(c) => {
+                let vars =
+                        match &c.path {
+                            Some(vars_path) => {
+                                let content =
+                                        match read_content(vars_path) {
+                                            Ok(c) => c,
+                                            Err(e) => panic!("{}", e),
+                                        };
+                                match read_document(&FileFormat::for_path(vars_path), &content).and_then(read_vars) {
+                                    Ok(v) => Some(v),
+                                    Err(e) => {
+                                        eprintln!("{}: wrong vars: {}", vars_path, e);
+                                        std::process::exit(21);
+                                    },
+                                }
+                            },
+                            None => None,
+                        };
+                let lookup = |name: &str| match &vars {
+                    Some(v) => v.get(name).cloned(),
+                    None => std::env::var(name).ok(),
+                };
+                let options = InterpolateOptions {
+                    strict: c.has_strict(),
+                    retype: c.has_retype(),
+                };
+                for (idx, val) in values.iter_mut().enumerate() {
+                    match interpolate::interpolate(val, &lookup, &options) {
+                        Ok(v) => *val = v,
+                        Err(e) => {
+                            eprintln!("document {}: {}", idx, e);
+                            std::process::exit(21);
+                        },
+                    }
+                } // for
+            },
+            Unit::Validate(c) => {
+                let schema_path = c.path.as_ref().unwrap();
+                let content =
+                        match read_content(schema_path) {
+                            Ok(c) => c,
+                            Err(e) => {
+                                eprintln!("{}: {}", schema_path, e);
+                                std::process::exit(21);
+                            },
+                        };
+                let schema =
+                        match read_document(&FileFormat::for_path(schema_path), &content).and_then(schema::Schema::new) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                eprintln!("{}: wrong schema: {}", schema_path, e);
+                                std::process::exit(21);
+                            },
+                        };
+
+                let mut is_valid = true;
+                for (idx, val) in values.iter_mut().enumerate() {
+                    if c.has_defaults() {
+                        *val = schema.fill_defaults(val);
+                    }
+                    for violation in schema.validate(val).iter() {
+                        eprintln!("document {}: '{}': {} (schema '{}')", idx, violation.instance_path, violation.message, violation.schema_path);
+                        is_valid = false;
+                    } // for
+                } // for
+                if !is_valid {
+                    std::process::exit(21);
+                }
+            },
+            Unit::JsonPatch => {
+                if values.len() < 2 {
+                    eprintln!("json-patch requires a document and a patch");
+                    std::process::exit(21);
+                }
+
+                let doc = values.pop_front().unwrap();
+                let json_patch = values.pop_front().unwrap();
+                match patch::apply_patch(&doc, &json_patch) {
+                    Ok(v) => values.push_front(v),
+                    Err(e) => {
+                        eprintln!("patch: {}", e);
+                        std::process::exit(21);
+                    },
+                }
+            },
             Unit::Pack => {
                 let mut res = Vec::new();
                 loop {
@@ -732,57 +1569,46 @@ fn main() {
                     }
                 } // loop
             },
-            Unit::Render(c) => {
+            Unit::CompileTemplate(c) => {
                 let template = template::Template::for_path(c.path.as_ref().unwrap());
-                let lua = rlua::Lua::new();
-                let input_values = values.clone();
-                values.clear();
-                let output_value =
-                        lua.context(|lua_ctx| {
-                            match lua_ctx.load(command::LUA_PRELUDE).exec() {
-                                Ok(_) => {},
-                                Err(e) => panic!("{}", e),
-                            }
-
-                            let globals = lua_ctx.globals();
-
-                            let ctx: rlua::Table =
-                                    match globals.get("ctx") {
-                                        Ok(v) => v,
-                                        Err(e) => panic!("{}", e),
-                                    };
-
-                            for (_, value) in input_values.iter().enumerate() {
-                                let mut sb = String::new();
-                                sb.push_str("table.insert(ctx.inputs,");
-                                sb.push_str(&value::to_lua_string(&value));
-                                sb.push_str(")");
-                                lua_ctx.load(&sb).exec().unwrap();
-                            } // for
-
-                            match lua_ctx.load(&template.content).exec() {
-                                Ok(_) => {},
-                                Err(e) => panic!("{}", e),
-                            }
-
-                            let outputs: rlua::Table =
-                                    match ctx.get("outputs") {
-                                        Ok(v) => v,
-                                        Err(e) => panic!("{}", e),
-                                    };
-
-                            match outputs.get(1).unwrap() {
-                                rlua::Value::Table(t) => Some(value::from_processed_template(t.clone())),
-                                _ => None,
-                            }
-                        });
-                if let Some(v) = output_value {
-                    values.push_back(Value::String(v));
-                }
+                values.push_back(Value::String(template.to_compiled()));
+            },
+            Unit::Render(c) => {
+                let path = c.path.as_ref().unwrap();
+                let engine = c.engine.unwrap_or_else(|| TemplateEngine::for_path(path));
+                let input_values: Vec<Value> = values.drain(..).collect();
+                let outputs =
+                        match engine {
+                            TemplateEngine::Lua => {
+                                let template = template::Template::for_path(path);
+                                render_all(&input_values, c.has_each(), |inputs, index| {
+                                    render_template(&template, inputs, index, c.has_strict())
+                                })
+                            },
+                            TemplateEngine::Jinja => {
+                                let template = jinja::JinjaTemplate::for_path(path);
+                                render_all(&input_values, c.has_each(), |inputs, index| {
+                                    match template.render(inputs, index, c.has_strict()) {
+                                        Ok(s) => Some(s),
+                                        Err(e) => {
+                                            eprintln!("{}", e);
+                                            std::process::exit(21);
+                                        },
+                                    }
+                                })
+                            },
+                        };
+                for output in outputs {
+                    match c.format {
+                        Some(ref f) => values.push_back(read_rendered_document(path, f, &output)),
+                        None => values.push_back(Value::String(output)),
+                    }
+                } // for
             },
             Unit::Transform(c) => {
+                let lua_path = c.path.as_ref().unwrap();
                 let lua_content =
-                        match std::fs::read_to_string(c.path.as_ref().unwrap()) {
+                        match std::fs::read_to_string(lua_path) {
                             Ok(c) => c,
                             Err(e) => panic!("{}", e),
                         };
@@ -804,17 +1630,33 @@ fn main() {
                                         Err(e) => panic!("{}", e),
                                     };
 
-                            for (_, value) in input_values.iter().enumerate() {
+                            for value in input_values.iter() {
                                 let mut sb = String::new();
                                 sb.push_str("table.insert(ctx.inputs,");
-                                sb.push_str(&value::to_lua_string(&value));
-                                sb.push_str(")");
+                                sb.push_str(&value::to_lua_string(value));
+                                sb.push(')');
                                 lua_ctx.load(&sb).exec().unwrap();
                             } // for
 
-                            match lua_ctx.load(&lua_content).exec() {
+                            if c.has_strict() {
+                                match lua_ctx.load(command::LUA_STRICT).exec() {
+                                    Ok(_) => {},
+                                    Err(e) => panic!("{}", e),
+                                }
+                            }
+
+                            let chunk_name = format!("={}", lua_path);
+                            let chunk =
+                                    match lua_ctx.load(&lua_content).set_name(&chunk_name) {
+                                        Ok(c) => c,
+                                        Err(e) => panic!("{}", e),
+                                    };
+                            match chunk.exec() {
                                 Ok(_) => {},
-                                Err(e) => panic!("{}", e),
+                                Err(e) => {
+                                    eprintln!("{}", command::lua_error_message(&e));
+                                    std::process::exit(21);
+                                },
                             }
 
                             let outputs: rlua::Table =
@@ -834,23 +1676,14 @@ fn main() {
                 break;
             },
         }
-    } // loop
+    } // while
 
     // (4ofx) Write output documents.
-    loop {
-        let unit =
-                match units.pop_front() {
-                    Some(u) => u,
-                    None => break,
-                };
+    while let Some(unit) = units.pop_front() {
         match unit {
             Unit::File(ref f) => {
                 // The number of values to process.
-                let mut val_cnt =
-                        match f.stream {
-                            Some(s) => s,
-                            None => 1,
-                        };
+                let mut val_cnt = f.stream.unwrap_or(1);
                 let mut output_content = String::new();
                 while val_cnt != 0 {
                     let val =
@@ -879,13 +1712,92 @@ fn main() {
                                         Value::Integer(v) => format!("{}", v),
                                         Value::Float(v) => format!("{}", v),
                                         Value::String(v) => v.clone(),
-                                        _ => panic!("wtf"),
+                                        _ => {
+                                            eprintln!("wrong output: plain requires a scalar");
+                                            std::process::exit(31);
+                                        },
                                     };
                             output_content.push_str(&buf);
                         },
+                        FileFormat::Env | FileFormat::Properties => {
+                            let flat_val =
+                                    match &f.separator {
+                                        Some(sep) => match flatten::flatten(&val, &flat_options(sep)) {
+                                            Ok(v) => v,
+                                            Err(e) => {
+                                                eprintln!("wrong output: {}", e);
+                                                std::process::exit(31);
+                                            },
+                                        },
+                                        None => val,
+                                    };
+                            let res =
+                                    if f.format == FileFormat::Env {
+                                        dotenv::to_string(&flat_val)
+                                    } else {
+                                        properties::to_string(&flat_val)
+                                    };
+                            match res {
+                                Ok(c) => output_content.push_str(&c),
+                                Err(e) => {
+                                    eprintln!("wrong output: {}", e);
+                                    std::process::exit(31);
+                                },
+                            }
+                        },
+                        FileFormat::FrontMatter => {
+                            match frontmatter::to_string(&val, f.meta.or(meta_format).unwrap_or_default()) {
+                                Ok(c) => output_content.push_str(&c),
+                                Err(e) => {
+                                    eprintln!("wrong output: {}", e);
+                                    std::process::exit(31);
+                                },
+                            }
+                        },
+                        FileFormat::Hcl => {
+                            match hcl::to_string(&val, f.has_blocks()) {
+                                Ok(c) => output_content.push_str(&c),
+                                Err(e) => {
+                                    eprintln!("wrong output: {}", e);
+                                    std::process::exit(31);
+                                },
+                            }
+                        },
+                        FileFormat::HtmlTable | FileFormat::MarkdownTable | FileFormat::Table => {
+                            let style =
+                                    match f.format {
+                                        FileFormat::HtmlTable => TableStyle::Html,
+                                        FileFormat::MarkdownTable => TableStyle::Markdown,
+                                        _ => TableStyle::Ascii,
+                                    };
+                            match table::to_string(&val, style, f.columns.as_deref()) {
+                                Ok(c) => output_content.push_str(&c),
+                                Err(e) => {
+                                    eprintln!("wrong output: {}", e);
+                                    std::process::exit(31);
+                                },
+                            }
+                        },
+                        FileFormat::Ini => {
+                            match ini::to_string(&val, &f.ini.clone().unwrap_or_default()) {
+                                Ok(c) => output_content.push_str(&c),
+                                Err(e) => {
+                                    eprintln!("wrong output: {}", e);
+                                    std::process::exit(31);
+                                },
+                            }
+                        },
                         FileFormat::Json => {
                             let buf =
-                                    if f.has_pretty() {
+                                    if f.has_canonical() {
+                                        match canonical::to_canonical_json(&val) {
+                                            Ok(c) => c,
+                                            Err(e) => {
+                                                eprintln!("wrong output: {}", e);
+                                                std::process::exit(31);
+                                            },
+                                        }
+                                    } else if f.has_pretty() {
                                         match serde_json::to_string_pretty(&val) {
                                             Ok(c) => c,
                                             Err(e) => panic!("{}", e),
@@ -902,6 +1814,9 @@ fn main() {
                             let buf = value::to_lua_string(&val);
                             output_content.push_str(&buf);
                         },
+                        FileFormat::Ron => {
+                            output_content.push_str(&ron::to_string(&val, f.has_pretty()));
+                        },
                         FileFormat::Toml => {
                             let fixed_val =
                                     if f.has_fix() {
@@ -946,10 +1861,14 @@ fn main() {
                 break;
             },
         }
-    } // loop
+    } // while
 
     if !values.is_empty() || !units.is_empty() {
         eprintln!("no output");
         std::process::exit(30);
     }
+
+    if has_differences {
+        std::process::exit(40);
+    }
 }
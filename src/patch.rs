@@ -0,0 +1,268 @@
+use indexmap::IndexMap;
+use crate::value::{self, Value};
+
+// Returns the index of a given Array token, as of RFC 6901.
+// The '-' token designates the end of the Array, when allowed.
+pub fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize, String> {
+    if token == "-" && allow_end {
+        return Ok(len);
+    }
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return Err(format!("wrong array index '{}'", token));
+    }
+
+    let limit = if allow_end { len + 1 } else { len };
+    match token.parse::<usize>() {
+        Ok(idx) if idx < limit => Ok(idx),
+        _ => Err(format!("array index '{}' out of bounds", token)),
+    }
+}
+
+// Parses a given JSON pointer.
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    match value::pointer_tokens(pointer) {
+        Ok(t) => Ok(t),
+        Err(_) => Err(format!("wrong path '{}'", pointer)),
+    }
+}
+
+// Returns the Value at a given path.
+pub fn get<'a>(value: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    let mut current = value;
+    for token in tokens.iter() {
+        current =
+                match current {
+                    Value::Array(a) => match array_index(token, a.len(), false) {
+                        Ok(idx) => &a[idx],
+                        Err(_) => return None,
+                    },
+                    Value::Object(o) => o.get(token)?,
+                    _ => return None,
+                };
+    } // for
+
+    Some(current)
+}
+
+// Returns the mutable Value at a given path.
+pub fn get_mut<'a>(value: &'a mut Value, tokens: &[String]) -> Option<&'a mut Value> {
+    let mut current = value;
+    for token in tokens.iter() {
+        current =
+                match current {
+                    Value::Array(a) => match array_index(token, a.len(), false) {
+                        Ok(idx) => &mut a[idx],
+                        Err(_) => return None,
+                    },
+                    Value::Object(o) => o.get_mut(token)?,
+                    _ => return None,
+                };
+    } // for
+
+    Some(current)
+}
+
+// Returns the JSON pointer of given tokens.
+pub fn to_pointer(tokens: &[String]) -> String {
+    tokens.iter().map(|t| format!("/{}", t.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+// Adds a Value at a given path.
+// An existing Object entry is replaced in place, an Array element is inserted.
+pub fn add(root: &mut Value, tokens: &[String], new_value: Value) -> Result<(), String> {
+    let (last, parent_tokens) =
+            match tokens.split_last() {
+                Some(s) => s,
+                None => {
+                    *root = new_value;
+                    return Ok(());
+                },
+            };
+    match get_mut(root, parent_tokens) {
+        Some(Value::Array(a)) => {
+            let idx = array_index(last, a.len(), true)?;
+            a.insert(idx, new_value);
+            Ok(())
+        },
+        Some(Value::Object(o)) => {
+            o.insert(last.clone(), new_value);
+            Ok(())
+        },
+        Some(_) => Err(format!("'{}' is not a container", to_pointer(parent_tokens))),
+        None => Err(format!("'{}' does not exist", to_pointer(parent_tokens))),
+    }
+}
+
+// Removes the Value at a given path and returns it.
+// The position of the remaining Object entries is kept.
+pub fn remove(root: &mut Value, tokens: &[String]) -> Result<Value, String> {
+    let (last, parent_tokens) =
+            match tokens.split_last() {
+                Some(s) => s,
+                None => return Err("cannot remove the root".to_owned()),
+            };
+    match get_mut(root, parent_tokens) {
+        Some(Value::Array(a)) => {
+            let idx = array_index(last, a.len(), false)?;
+            Ok(a.remove(idx))
+        },
+        Some(Value::Object(o)) => match o.shift_remove(last) {
+            Some(v) => Ok(v),
+            None => Err(format!("'{}' does not exist", to_pointer(tokens))),
+        },
+        _ => Err(format!("'{}' does not exist", to_pointer(tokens))),
+    }
+}
+
+// Tests whether 2 given Values are equal as JSON values, where 1 and 1.0 are equal.
+fn json_equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Integer(l), Value::Float(r)) => (*l as f64) == *r,
+        (Value::Float(l), Value::Integer(r)) => *l == (*r as f64),
+        (Value::Array(l), Value::Array(r)) => l.len() == r.len() && l.iter().zip(r).all(|(a, b)| json_equals(a, b)),
+        (Value::Object(l), Value::Object(r)) => l.len() == r.len() && l.iter().all(|(k, v)| match r.get(k) {
+            Some(rv) => json_equals(v, rv),
+            None => false,
+        }),
+        _ => left == right,
+    }
+}
+
+// Returns a given string member of a patch operation.
+fn member<'a>(op: &'a IndexMap<String, Value>, name: &str) -> Result<&'a str, String> {
+    match op.get(name) {
+        Some(Value::String(s)) => Ok(s),
+        Some(_) => Err(format!("'{}' is not a string", name)),
+        None => Err(format!("missing '{}'", name)),
+    }
+}
+
+// Applies a given patch operation to a given document.
+fn apply_operation(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let op =
+            match op {
+                Value::Object(o) => o,
+                _ => return Err("not an object".to_owned()),
+            };
+    let path = parse_pointer(member(op, "path")?)?;
+    let new_value = op.get("value");
+
+    match member(op, "op")? {
+        "add" => match new_value {
+            Some(v) => add(doc, &path, v.clone()),
+            None => Err("missing 'value'".to_owned()),
+        },
+        "remove" => remove(doc, &path).map(|_| ()),
+        "replace" => match new_value {
+            Some(v) => match get_mut(doc, &path) {
+                Some(old) => {
+                    *old = v.clone();
+                    Ok(())
+                },
+                None => Err(format!("'{}' does not exist", to_pointer(&path))),
+            },
+            None => Err("missing 'value'".to_owned()),
+        },
+        "move" => {
+            let from = parse_pointer(member(op, "from")?)?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err(format!("cannot move '{}' into itself", to_pointer(&from)));
+            }
+            let v = remove(doc, &from)?;
+            add(doc, &path, v)
+        },
+        "copy" => {
+            let from = parse_pointer(member(op, "from")?)?;
+            let v =
+                    match get(doc, &from) {
+                        Some(v) => v.clone(),
+                        None => return Err(format!("'{}' does not exist", to_pointer(&from))),
+                    };
+            add(doc, &path, v)
+        },
+        "test" => match new_value {
+            Some(v) => match get(doc, &path) {
+                Some(actual) if json_equals(actual, v) => Ok(()),
+                Some(_) => Err(format!("test failed at '{}'", to_pointer(&path))),
+                None => Err(format!("test failed, '{}' does not exist", to_pointer(&path))),
+            },
+            None => Err("missing 'value'".to_owned()),
+        },
+        other => Err(format!("unknown operation '{}'", other)),
+    }
+}
+
+// Creates a new Value by applying a given JSON patch to a given document, as of RFC 6902.
+// The operations are applied atomically: on error, the message names the index of the failing operation.
+pub fn apply_patch(doc: &Value, patch: &Value) -> Result<Value, String> {
+    let ops =
+            match patch {
+                Value::Array(a) => a,
+                _ => return Err("the patch is not an array".to_owned()),
+            };
+
+    let mut res = doc.clone();
+    for (idx, op) in ops.iter().enumerate() {
+        if let Err(e) = apply_operation(&mut res, op) {
+            return Err(format!("operation {}: {}", idx, e));
+        }
+    } // for
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod apply_patch {
+        use super::*;
+
+        #[test]
+        fn it_adds_values() {
+            let doc = new_value(r#"{"a":1,"b":[1,2]}"#);
+            let patch = new_value(r#"[{"op":"add","path":"/c","value":3},{"op":"add","path":"/b/1","value":9},{"op":"add","path":"/b/-","value":4}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Ok(new_value(r#"{"a":1,"b":[1,9,2,4],"c":3}"#)));
+        }
+
+        #[test]
+        fn it_keeps_key_order() {
+            let doc = new_value(r#"{"a":1,"b":2,"c":3}"#);
+            let patch = new_value(r#"[{"op":"replace","path":"/a","value":0},{"op":"remove","path":"/b"},{"op":"add","path":"/c","value":4}]"#);
+            // The Object equality ignores the key order, unlike the serialized documents.
+            let res = apply_patch(&doc, &patch).unwrap();
+            assert_eq!(serde_json::to_string(&res).unwrap(), r#"{"a":0,"c":4}"#);
+            let patch = new_value(r#"[{"op":"remove","path":"/a"},{"op":"add","path":"/a","value":0}]"#);
+            let res = apply_patch(&doc, &patch).unwrap();
+            assert_eq!(serde_json::to_string(&res).unwrap(), r#"{"b":2,"c":3,"a":0}"#);
+        }
+
+        #[test]
+        fn it_moves_and_copies_values() {
+            let doc = new_value(r#"{"a":{"b":1},"c":[]}"#);
+            let patch = new_value(r#"[{"op":"copy","from":"/a/b","path":"/c/0"},{"op":"move","from":"/a","path":"/d"}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Ok(new_value(r#"{"c":[1],"d":{"b":1}}"#)));
+        }
+
+        #[test]
+        fn it_escapes_tokens() {
+            let doc = new_value(r#"{"a/b":{"m~n":1}}"#);
+            let patch = new_value(r#"[{"op":"test","path":"/a~1b/m~0n","value":1.0}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Ok(doc));
+        }
+
+        #[test]
+        fn it_reports_failing_operations() {
+            let doc = new_value(r#"{"a":1}"#);
+            let patch = new_value(r#"[{"op":"test","path":"/a","value":1},{"op":"test","path":"/a","value":2}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Err("operation 1: test failed at '/a'".to_owned()));
+
+            let patch = new_value(r#"[{"op":"remove","path":"/b"}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Err("operation 0: '/b' does not exist".to_owned()));
+
+            let patch = new_value(r#"[{"op":"add","path":"/a/b/c","value":1}]"#);
+            assert_eq!(apply_patch(&doc, &patch), Err("operation 0: '/a/b' does not exist".to_owned()));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use indexmap::IndexMap;
+use crate::value::{self, Value};
+
+// Returns the logical lines of a given properties document, with the number of their first line.
+// A line ending with an odd number of backslashes continues on the next line, without its leading whitespaces.
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut res = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let line =
+                match current {
+                    Some(_) => line.trim_start_matches([' ', '\t', '\u{c}']),
+                    None => {
+                        let l = line.trim_start_matches([' ', '\t', '\u{c}']);
+                        if l.is_empty() || l.starts_with(['#', '!']) {
+                            continue;
+                        }
+                        l
+                    },
+                };
+        let (nb, mut sb) = current.take().unwrap_or((idx + 1, String::new()));
+        let backslashes = line.len() - line.trim_end_matches('\\').len();
+        if backslashes % 2 == 1 {
+            sb.push_str(&line[..line.len() - 1]);
+            current = Some((nb, sb));
+        } else {
+            sb.push_str(line);
+            res.push((nb, sb));
+        }
+    } // for
+    if let Some(l) = current {
+        res.push(l);
+    }
+
+    res
+}
+
+// Unescapes a given key or value.
+fn unescape(content: &str) -> Result<String, String> {
+    let mut sb = String::new();
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            sb.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => sb.push('\t'),
+            Some('n') => sb.push('\n'),
+            Some('r') => sb.push('\r'),
+            Some('f') => sb.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let unit =
+                        match u16::from_str_radix(&hex, 16) {
+                            Ok(u) if hex.len() == 4 => u,
+                            _ => return Err(format!("wrong unicode escape '\\u{}'", hex)),
+                        };
+                // Supplementary characters are written as surrogate pairs.
+                let mut units = vec![unit];
+                if (0xD800..0xDC00).contains(&unit) && chars.as_str().starts_with("\\u") {
+                    let low = u16::from_str_radix(chars.as_str().get(2..6).unwrap_or_default(), 16);
+                    if let Ok(l) = low {
+                        units.push(l);
+                        chars.by_ref().take(6).for_each(drop);
+                    }
+                }
+                match String::from_utf16(&units) {
+                    Ok(s) => sb.push_str(&s),
+                    Err(_) => return Err(format!("wrong unicode escape '\\u{}'", hex)),
+                }
+            },
+            Some(e) => sb.push(e),
+            None => {},
+        }
+    } // while
+
+    Ok(sb)
+}
+
+// Reads a Java properties document into a flat Object of strings.
+pub fn from_str(content: &str) -> Result<Value, String> {
+    let mut res = IndexMap::new();
+    for (nb, line) in logical_lines(content.strip_prefix('\u{FEFF}').unwrap_or(content)).iter() {
+        // The key ends with the first unescaped separator or whitespace.
+        let mut end = line.len();
+        let mut escaped = false;
+        for (idx, c) in line.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if "=: \t\u{c}".contains(c) {
+                end = idx;
+                break;
+            }
+        } // for
+        let rest = line[end..].trim_start_matches([' ', '\t', '\u{c}']);
+        let rest =
+                match rest.strip_prefix(['=', ':']) {
+                    Some(r) => r.trim_start_matches([' ', '\t', '\u{c}']),
+                    None => rest,
+                };
+
+        let key = unescape(&line[..end]).map_err(|e| format!("line {}: {}", nb, e))?;
+        let value = unescape(rest).map_err(|e| format!("line {}: {}", nb, e))?;
+        res.insert(key, Value::String(value));
+    } // for
+
+    Ok(Value::Object(res))
+}
+
+// Escapes a given key or value, whose non-ASCII characters are unicode escapes.
+// The whitespaces of a value are escaped when leading only.
+fn escape(content: &str, is_key: bool) -> String {
+    let mut sb = String::new();
+    for (idx, c) in content.chars().enumerate() {
+        match c {
+            '\t' => sb.push_str("\\t"),
+            '\n' => sb.push_str("\\n"),
+            '\r' => sb.push_str("\\r"),
+            '\u{c}' => sb.push_str("\\f"),
+            '\\' | '=' | ':' | '#' | '!' => {
+                sb.push('\\');
+                sb.push(c);
+            },
+            ' ' if is_key || idx == 0 => sb.push_str("\\ "),
+            ' '..='~' => sb.push(c),
+            _ => {
+                let mut units = [0; 2];
+                for u in c.encode_utf16(&mut units).iter() {
+                    sb.push_str(&format!("\\u{:04X}", u));
+                } // for
+            },
+        }
+    } // for
+
+    sb
+}
+
+// Writes a flat Object of scalars as a Java properties document.
+pub fn to_string(value: &Value) -> Result<String, String> {
+    let o =
+            match value {
+                Value::Object(o) => o,
+                _ => return Err("properties requires an object".to_owned()),
+            };
+
+    let mut sb = String::new();
+    for (k, v) in o.iter() {
+        let v =
+                match value::scalar_to_string(v) {
+                    Some(s) => s,
+                    None => return Err(format!("nested value at '{}'", k)),
+                };
+        sb.push_str(&escape(k, true));
+        sb.push('=');
+        sb.push_str(&escape(&v, false));
+        sb.push('\n');
+    } // for
+
+    Ok(sb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn it_reads_entries() {
+            let content = concat!(
+                "# comment\n",
+                "! comment\n",
+                "db.host = db.local\n",
+                "db.port:5432\n",
+                "  empty\n",
+                "my\\ key\\=a = a\\tb\\u00e9\\ud83d\\ude00\n",
+                "list = a, \\\n",
+                "       b, \\\\\n",
+                "path c:\\\\x\n",
+            );
+
+            let res = from_str(content);
+            assert_eq!(res, Ok(new_value("{\"db.host\":\"db.local\",\"db.port\":\"5432\",\"empty\":\"\",\"my key=a\":\"a\\tb\u{e9}\u{1f600}\",\"list\":\"a, b, \\\\\",\"path\":\"c:\\\\x\"}")));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_escapes() {
+            assert_eq!(from_str("a\nb=\\u12\n"), Err("line 2: wrong unicode escape '\\u12'".to_owned()));
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_escapes_entries() {
+            let doc = new_value("{\"my key\":\" a b\",\"c\":\"x=y\\n\u{e9}\u{1f600}\",\"d\":1.5,\"e\":null}");
+
+            let res = to_string(&doc);
+            assert_eq!(res, Ok("my\\ key=\\ a b\nc=x\\=y\\n\\u00E9\\uD83D\\uDE00\nd=1.5\ne=\n".to_owned()));
+            assert_eq!(from_str(&res.unwrap()), Ok(new_value("{\"my key\":\" a b\",\"c\":\"x=y\\n\u{e9}\u{1f600}\",\"d\":\"1.5\",\"e\":\"\"}")));
+            assert_eq!(to_string(&new_value(r#"{"a":[1]}"#)), Err("nested value at 'a'".to_owned()));
+        }
+    }
+}
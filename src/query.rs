@@ -0,0 +1,674 @@
+use crate::value::Value;
+
+// A way of selecting the children of a Value.
+#[derive(Debug, PartialEq)]
+enum Selector {
+    // An Object entry.
+    Name(String),
+    // Every Object entry or Array element.
+    Wildcard,
+    // An Array element, counted from the end when negative.
+    Index(i64),
+    // Array elements, from a start to an excluded end by a step.
+    Slice(Option<i64>, Option<i64>, i64),
+    // Every Object entry or Array element matching an expression.
+    Filter(Box<Expr>),
+}
+
+// A step of a query.
+#[derive(Debug, PartialEq)]
+struct Segment {
+    // Applies the selectors to the Value and to all its descendants.
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Operand {
+    Literal(Value),
+    // A path relative to the current Value ('@'), or to the root Value ('$').
+    Path(bool, Vec<Segment>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// A filter expression.
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Comparison, Operand),
+    // Tests whether a path selects anything.
+    Exists(bool, Vec<Segment>),
+}
+
+// A JSONPath query, as of RFC 9535.
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error<T>(&self, msg: &str) -> Result<T, String> {
+        Err(format!("{} at position {}", msg, self.pos))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        } // while
+    }
+
+    // Consumes a given string when it comes next.
+    fn accept(&mut self, s: &str) -> bool {
+        let len = s.chars().count();
+        if self.chars.len() >= self.pos + len && self.chars[self.pos..self.pos + len].iter().copied().eq(s.chars()) {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), String> {
+        if self.accept(s) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", s))
+        }
+    }
+
+    fn is_name_char(c: char, first: bool) -> bool {
+        c.is_alphabetic() || c == '_' || !c.is_ascii() || (!first && (c.is_ascii_digit() || c == '-'))
+    }
+
+    fn parse_name(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !Parser::is_name_char(c, self.pos == start) {
+                break;
+            }
+            self.pos += 1;
+        } // while
+        if self.pos == start {
+            return self.error("expected a name");
+        }
+
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap();
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None => return self.error("unterminated string"),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(s);
+                },
+                Some('\\') => {
+                    self.pos += 1;
+                    let c =
+                            match self.peek() {
+                                Some('n') => '\n',
+                                Some('r') => '\r',
+                                Some('t') => '\t',
+                                Some('b') => '\u{8}',
+                                Some('f') => '\u{c}',
+                                Some('u') => {
+                                    let hex: String = self.chars.iter().skip(self.pos + 1).take(4).collect();
+                                    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                        Some(c) if hex.len() == 4 => {
+                                            self.pos += 4;
+                                            c
+                                        },
+                                        _ => return self.error("wrong unicode escape"),
+                                    }
+                                },
+                                Some(c) => c,
+                                None => return self.error("unterminated string"),
+                            };
+                    self.pos += 1;
+                    s.push(c);
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    s.push(c);
+                },
+            }
+        } // loop
+    }
+
+    // Parses a number, either an integer or a float.
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                self.pos += 1;
+            } else if c == '.' || c == 'e' || c == 'E' || ((c == '+' || c == '-') && is_float) {
+                is_float = true;
+                self.pos += 1;
+            } else {
+                break;
+            }
+        } // while
+
+        let s: String = self.chars[start..self.pos].iter().collect();
+        if is_float {
+            match s.parse::<f64>() {
+                Ok(f) => Ok(Value::Float(f)),
+                Err(_) => self.error("wrong number"),
+            }
+        } else {
+            match s.parse::<i64>() {
+                Ok(i) => Ok(Value::Integer(i)),
+                Err(_) => self.error("wrong number"),
+            }
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<i64, String> {
+        match self.parse_number()? {
+            Value::Integer(i) => Ok(i),
+            _ => self.error("expected an integer"),
+        }
+    }
+
+    fn is_integer_start(&self) -> bool {
+        match self.peek() {
+            Some('-') => matches!(self.peek_at(1), Some(c) if c.is_ascii_digit()),
+            Some(c) => c.is_ascii_digit(),
+            None => false,
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, String> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.parse_string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            },
+            Some('?') => {
+                self.pos += 1;
+                self.skip_whitespaces();
+                Ok(Selector::Filter(Box::new(self.parse_or()?)))
+            },
+            _ => {
+                let start =
+                        if self.is_integer_start() {
+                            Some(self.parse_integer()?)
+                        } else {
+                            None
+                        };
+                self.skip_whitespaces();
+                if !self.accept(":") {
+                    return match start {
+                        Some(idx) => Ok(Selector::Index(idx)),
+                        None => self.error("expected a selector"),
+                    };
+                }
+
+                self.skip_whitespaces();
+                let end =
+                        if self.is_integer_start() {
+                            Some(self.parse_integer()?)
+                        } else {
+                            None
+                        };
+                self.skip_whitespaces();
+                let mut step = 1;
+                if self.accept(":") {
+                    self.skip_whitespaces();
+                    if self.is_integer_start() {
+                        step = self.parse_integer()?;
+                    }
+                }
+                Ok(Selector::Slice(start, end, step))
+            },
+        }
+    }
+
+    fn parse_selectors(&mut self) -> Result<Vec<Selector>, String> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespaces();
+            selectors.push(self.parse_selector()?);
+            self.skip_whitespaces();
+            if self.accept("]") {
+                return Ok(selectors);
+            }
+            self.expect(",")?;
+        } // loop
+    }
+
+    // Parses the segments following a root identifier.
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, String> {
+        let mut segments = Vec::new();
+        loop {
+            let descendant = self.accept("..");
+            let selectors =
+                    if self.accept("[") {
+                        self.parse_selectors()?
+                    } else if descendant || self.accept(".") {
+                        if self.accept("*") {
+                            vec![Selector::Wildcard]
+                        } else {
+                            vec![Selector::Name(self.parse_name()?)]
+                        }
+                    } else {
+                        return Ok(segments);
+                    };
+            segments.push(Segment {
+                descendant,
+                selectors,
+            });
+        } // loop
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.accept("||") {
+            self.skip_whitespaces();
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        } // while
+
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while self.accept("&&") {
+            self.skip_whitespaces();
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        } // while
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        let expr =
+                if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+                    self.pos += 1;
+                    self.skip_whitespaces();
+                    Expr::Not(Box::new(self.parse_unary()?))
+                } else if self.accept("(") {
+                    self.skip_whitespaces();
+                    let expr = self.parse_or()?;
+                    self.expect(")")?;
+                    expr
+                } else {
+                    let left = self.parse_operand()?;
+                    self.skip_whitespaces();
+                    let comparison =
+                            if self.accept("==") {
+                                Some(Comparison::Eq)
+                            } else if self.accept("!=") {
+                                Some(Comparison::Ne)
+                            } else if self.accept("<=") {
+                                Some(Comparison::Le)
+                            } else if self.accept("<") {
+                                Some(Comparison::Lt)
+                            } else if self.accept(">=") {
+                                Some(Comparison::Ge)
+                            } else if self.accept(">") {
+                                Some(Comparison::Gt)
+                            } else {
+                                None
+                            };
+                    match (left, comparison) {
+                        (left, Some(c)) => {
+                            self.skip_whitespaces();
+                            Expr::Compare(left, c, self.parse_operand()?)
+                        },
+                        (Operand::Path(absolute, segments), None) => Expr::Exists(absolute, segments),
+                        (Operand::Literal(_), None) => return self.error("expected a comparison"),
+                    }
+                };
+        self.skip_whitespaces();
+
+        Ok(expr)
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.peek() {
+            Some('@') | Some('$') => {
+                let absolute = self.peek() == Some('$');
+                self.pos += 1;
+                Ok(Operand::Path(absolute, self.parse_segments()?))
+            },
+            Some('\'') | Some('"') => Ok(Operand::Literal(Value::String(self.parse_string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Operand::Literal(self.parse_number()?)),
+            _ => {
+                if self.accept("true") {
+                    Ok(Operand::Literal(Value::Boolean(true)))
+                } else if self.accept("false") {
+                    Ok(Operand::Literal(Value::Boolean(false)))
+                } else if self.accept("null") {
+                    Ok(Operand::Literal(Value::Nil))
+                } else {
+                    self.error("expected a value")
+                }
+            },
+        }
+    }
+}
+
+impl Query {
+    // Parses a given query expression.
+    // The leading '$' is optional, so that 'spec.containers[0]' and '.spec.containers[0]' are valid queries too.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: expr.trim().chars().collect(),
+            pos: 0,
+        };
+        if !parser.accept("$") && parser.peek().is_some_and(|c| Parser::is_name_char(c, true)) {
+            let name = parser.parse_name()?;
+            parser.pos -= name.chars().count();
+            parser.chars.insert(parser.pos, '.');
+        }
+        let segments = parser.parse_segments()?;
+        if parser.pos < parser.chars.len() {
+            return parser.error("unexpected character");
+        }
+
+        Ok(Query {
+            segments,
+        })
+    }
+
+    // Returns the Values selected from a given root Value, in document order.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        select_segments(&self.segments, root, root)
+    }
+}
+
+// Returns the children of a given Value.
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(a) => a.iter().collect(),
+        Value::Object(o) => o.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Returns a given Value and all its descendants, in document order.
+fn descendants<'a>(value: &'a Value, res: &mut Vec<&'a Value>) {
+    res.push(value);
+    for child in children(value) {
+        descendants(child, res);
+    } // for
+}
+
+fn select_segments<'a>(segments: &[Segment], current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+    let mut nodes = vec![current];
+    for segment in segments.iter() {
+        let mut next = Vec::new();
+        for node in nodes {
+            let targets =
+                    if segment.descendant {
+                        let mut d = Vec::new();
+                        descendants(node, &mut d);
+                        d
+                    } else {
+                        vec![node]
+                    };
+            for target in targets {
+                for selector in segment.selectors.iter() {
+                    apply_selector(selector, target, root, &mut next);
+                } // for
+            } // for
+        } // for
+        nodes = next;
+    } // for
+
+    nodes
+}
+
+// Returns the index of an Array element, counted from the end when negative.
+fn normalize_index(idx: i64, len: i64) -> i64 {
+    if idx >= 0 {
+        idx
+    } else {
+        len + idx
+    }
+}
+
+fn apply_selector<'a>(selector: &Selector, value: &'a Value, root: &'a Value, res: &mut Vec<&'a Value>) {
+    match selector {
+        Selector::Name(name) => {
+            if let Value::Object(o) = value {
+                if let Some(v) = o.get(name) {
+                    res.push(v);
+                }
+            }
+        },
+        Selector::Wildcard => res.extend(children(value)),
+        Selector::Index(idx) => {
+            if let Value::Array(a) = value {
+                let idx = normalize_index(*idx, a.len() as i64);
+                if idx >= 0 && idx < a.len() as i64 {
+                    res.push(&a[idx as usize]);
+                }
+            }
+        },
+        Selector::Slice(start, end, step) => {
+            if let Value::Array(a) = value {
+                let len = a.len() as i64;
+                let step = *step;
+                if step > 0 {
+                    let lower = normalize_index(start.unwrap_or(0), len).clamp(0, len);
+                    let upper = normalize_index(end.unwrap_or(len), len).clamp(0, len);
+                    let mut idx = lower;
+                    while idx < upper {
+                        res.push(&a[idx as usize]);
+                        // A step beyond the bounds of i64 ends the slice.
+                        idx = match idx.checked_add(step) {
+                            Some(i) => i,
+                            None => break,
+                        };
+                    } // while
+                } else if step < 0 {
+                    let upper = start.map_or(len - 1, |s| normalize_index(s, len)).clamp(-1, len - 1);
+                    let lower = end.map_or(-1, |e| normalize_index(e, len)).clamp(-1, len - 1);
+                    let mut idx = upper;
+                    while lower < idx {
+                        res.push(&a[idx as usize]);
+                        // A step beyond the bounds of i64 ends the slice.
+                        idx = match idx.checked_add(step) {
+                            Some(i) => i,
+                            None => break,
+                        };
+                    } // while
+                }
+            }
+        },
+        Selector::Filter(expr) => {
+            for child in children(value) {
+                if eval_expr(expr, child, root) {
+                    res.push(child);
+                }
+            } // for
+        },
+    }
+}
+
+// Returns the Value of a comparison operand, if it designates exactly one Value.
+fn eval_operand<'a>(operand: &'a Operand, current: &'a Value, root: &'a Value) -> Option<&'a Value> {
+    match operand {
+        Operand::Literal(v) => Some(v),
+        Operand::Path(absolute, segments) => {
+            let start = if *absolute { root } else { current };
+            let nodes = select_segments(segments, start, root);
+            if nodes.len() == 1 {
+                Some(nodes[0])
+            } else {
+                None
+            }
+        },
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (as_number(left), as_number(right)) {
+        (Some(l), Some(r)) => l == r,
+        _ => match (left, right) {
+            (Value::Array(l), Value::Array(r)) => l.len() == r.len() && l.iter().zip(r).all(|(a, b)| equals(a, b)),
+            (Value::Object(l), Value::Object(r)) => l.len() == r.len() && l.iter().all(|(k, v)| r.get(k).is_some_and(|rv| equals(v, rv))),
+            _ => left == right,
+        },
+    }
+}
+
+fn less_than(left: &Value, right: &Value) -> bool {
+    match (as_number(left), as_number(right)) {
+        (Some(l), Some(r)) => l < r,
+        _ => match (left, right) {
+            (Value::String(l), Value::String(r)) => l < r,
+            _ => false,
+        },
+    }
+}
+
+fn compare(left: Option<&Value>, comparison: Comparison, right: Option<&Value>) -> bool {
+    match comparison {
+        Comparison::Eq => match (left, right) {
+            (Some(l), Some(r)) => equals(l, r),
+            (None, None) => true,
+            _ => false,
+        },
+        Comparison::Ne => !compare(left, Comparison::Eq, right),
+        Comparison::Lt => match (left, right) {
+            (Some(l), Some(r)) => less_than(l, r),
+            _ => false,
+        },
+        Comparison::Le => compare(left, Comparison::Lt, right) || compare(left, Comparison::Eq, right),
+        Comparison::Gt => compare(right, Comparison::Lt, left),
+        Comparison::Ge => compare(right, Comparison::Le, left),
+    }
+}
+
+fn eval_expr(expr: &Expr, current: &Value, root: &Value) -> bool {
+    match expr {
+        Expr::Or(l, r) => eval_expr(l, current, root) || eval_expr(r, current, root),
+        Expr::And(l, r) => eval_expr(l, current, root) && eval_expr(r, current, root),
+        Expr::Not(e) => !eval_expr(e, current, root),
+        Expr::Compare(l, c, r) => compare(eval_operand(l, current, root), *c, eval_operand(r, current, root)),
+        Expr::Exists(absolute, segments) => {
+            let start = if *absolute { root } else { current };
+            !select_segments(segments, start, root).is_empty()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    fn query(expr: &str, json: &str) -> Value {
+        let root = new_value(json);
+        let res = Query::parse(expr).unwrap().select(&root).into_iter().cloned().collect();
+
+        Value::Array(res)
+    }
+
+    const STORE: &str = r#"{"store":{"book":[
+        {"category":"reference","author":"Nigel Rees","price":8.95},
+        {"category":"fiction","author":"Evelyn Waugh","price":12.99},
+        {"category":"fiction","author":"Herman Melville","isbn":"0-553-21311-3","price":8.99}
+    ],"bicycle":{"color":"red","price":399}}}"#;
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn it_fails_on_wrong_queries() {
+            assert_eq!(Query::parse("$.a[").unwrap_err(), "expected a selector at position 4");
+            assert_eq!(Query::parse("$.a b").unwrap_err(), "unexpected character at position 3");
+            assert_eq!(Query::parse("$[?(@.a == 1]").unwrap_err(), "expected ')' at position 12");
+        }
+    }
+
+    mod select {
+        use super::*;
+
+        #[test]
+        fn it_selects_names_and_indexes() {
+            assert_eq!(query("$.store.book[0].author", STORE), new_value(r#"["Nigel Rees"]"#));
+            assert_eq!(query("store.book[-1]['author']", STORE), new_value(r#"["Herman Melville"]"#));
+            assert_eq!(query(".store.bicycle.*", STORE), new_value(r#"["red",399]"#));
+        }
+
+        #[test]
+        fn it_selects_slices() {
+            let json = "[0,1,2,3,4,5]";
+            assert_eq!(query("$[1:3]", json), new_value("[1,2]"));
+            assert_eq!(query("$[::2]", json), new_value("[0,2,4]"));
+            assert_eq!(query("$[-2:]", json), new_value("[4,5]"));
+            assert_eq!(query("$[::-2]", json), new_value("[5,3,1]"));
+            assert_eq!(query("$[0, 3:4]", json), new_value("[0,3]"));
+        }
+
+        #[test]
+        fn it_selects_slices_with_extreme_steps() {
+            let json = "[0,1,2,3,4,5]";
+            assert_eq!(query("$[1::9223372036854775807]", json), new_value("[1]"));
+            assert_eq!(query("$[::-9223372036854775807]", json), new_value("[5]"));
+            assert_eq!(query("$[0::-9223372036854775808]", json), new_value("[0]"));
+        }
+
+        #[test]
+        fn it_selects_descendants() {
+            assert_eq!(query("$..author", STORE), new_value(r#"["Nigel Rees","Evelyn Waugh","Herman Melville"]"#));
+            assert_eq!(query("$..price", STORE), new_value("[8.95,12.99,8.99,399]"));
+        }
+
+        #[test]
+        fn it_filters() {
+            assert_eq!(query("$.store.book[?@.price < 10].author", STORE), new_value(r#"["Nigel Rees","Herman Melville"]"#));
+            assert_eq!(query("$..book[?(@.isbn)].price", STORE), new_value("[8.99]"));
+            assert_eq!(query("$..book[?@.category == 'fiction' && !(@.price > 10)].author", STORE), new_value(r#"["Herman Melville"]"#));
+            assert_eq!(query("$..book[?@.price > $.store.bicycle.price]", STORE), new_value("[]"));
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use indexmap::IndexMap;
+use crate::edit;
+use crate::patch;
+use crate::value::Value;
+
+const REF_KEY: &str = "$ref";
+
+// The location of a Value: the index of its document and the tokens of its path.
+type Location = (usize, Vec<String>);
+
+// Returns the location of the reference of a given string, relative to a given document:
+// - '.PATH' for a path of the document, either dotted or a JSON pointer after the dot,
+// - 'N.PATH' for a path of the document N.
+// Returns None when the string is not a reference.
+fn parse_string_ref(expr: &str, doc: usize) -> Option<Result<Location, String>> {
+    let dot = expr.find('.')?;
+    let target =
+            match &expr[..dot] {
+                "" => doc,
+                idx if idx.bytes().all(|b| b.is_ascii_digit()) => idx.parse::<usize>().ok()?,
+                _ => return None,
+            };
+
+    Some(edit::parse_path(&expr[dot + 1..]).map(|tokens| (target, tokens)))
+}
+
+// Returns the location of a given '$ref' value, relative to a given document:
+// - '#POINTER' for a JSON pointer of the document,
+// - 'N#POINTER' for a JSON pointer of the document N.
+// Returns None for the other references, such as 'other.json#/a' or URLs, which are not local.
+fn parse_object_ref(reference: &str, doc: usize) -> Option<Result<Location, String>> {
+    let (idx, pointer) = reference.split_once('#')?;
+    let target =
+            match idx {
+                "" => doc,
+                idx if idx.bytes().all(|b| b.is_ascii_digit()) => idx.parse::<usize>().ok()?,
+                _ => return None,
+            };
+
+    Some(patch::parse_pointer(pointer).map(|tokens| (target, tokens)))
+}
+
+// Returns the '$ref' value of a given Object made of this entry only.
+fn object_ref(o: &IndexMap<String, Value>) -> Option<&Value> {
+    match o.len() {
+        1 => o.get(REF_KEY),
+        _ => None,
+    }
+}
+
+// Returns the expressions of the '${...}' placeholders of a given string, with their byte ranges.
+// The '$${' sequence escapes a placeholder, and is unescaped by unescape.
+fn placeholders(content: &str) -> Vec<(usize, usize, &str)> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while let Some(idx) = content[pos..].find("${") {
+        let start = pos + idx;
+        if start > 0 && content.as_bytes()[start - 1] == b'$' {
+            pos = start + 2;
+            continue;
+        }
+        match content[start..].find('}') {
+            Some(len) => {
+                res.push((start, start + len + 1, &content[start + 2..start + len]));
+                pos = start + len + 1;
+            },
+            None => break,
+        }
+    } // while
+
+    res
+}
+
+// Turns the '$${' sequences of a given string into '${'.
+fn unescape(content: &str) -> String {
+    content.replace("$${", "${")
+}
+
+struct Resolver<'a> {
+    docs: &'a [Value],
+    // The resolved Values of the locations already resolved.
+    resolved: HashMap<Location, Value>,
+    // The locations being resolved, to detect cycles.
+    stack: Vec<Location>,
+    // The locations being looked up, to detect cycles along paths.
+    lookups: Vec<Location>,
+}
+
+impl<'a> Resolver<'a> {
+    fn describe(location: &Location) -> String {
+        format!("{}#{}", location.0, patch::to_pointer(&location.1))
+    }
+
+    fn cycle(locations: &[Location], location: &Location) -> String {
+        let cycle: Vec<String> = locations.iter().chain(Some(location)).map(Resolver::describe).collect();
+        format!("reference cycle {}", cycle.join(" -> "))
+    }
+
+    // Returns the location of the reference made by a given Value of a given document, if any.
+    fn reference(value: &Value, doc: usize) -> Option<Result<Location, String>> {
+        match value {
+            Value::String(s) => match placeholders(s).as_slice() {
+                [(0, end, expr)] if *end == s.len() => parse_string_ref(expr, doc),
+                _ => None,
+            },
+            Value::Object(o) => match object_ref(o)? {
+                Value::String(r) => parse_object_ref(r, doc),
+                _ => Some(Err(format!("'{}' is not a string", REF_KEY))),
+            },
+            _ => None,
+        }
+    }
+
+    // Returns the unresolved Value at a given location, and its actual location.
+    // The references along the path are followed.
+    fn lookup(&mut self, location: &Location) -> Result<(Location, &'a Value), String> {
+        if let Some(pos) = self.lookups.iter().position(|l| l == location) {
+            return Err(Resolver::cycle(&self.lookups[pos..], location));
+        }
+
+        let (doc, tokens) = location;
+        let docs = self.docs;
+        let mut value =
+                match docs.get(*doc) {
+                    Some(d) => d,
+                    None => return Err(format!("dangling reference '{}': no document {}", Resolver::describe(location), doc)),
+                };
+        let mut current = (*doc, Vec::new());
+        self.lookups.push(location.clone());
+        for token in tokens.iter() {
+            if let Some(target) = Resolver::reference(value, current.0) {
+                let found = target.and_then(|t| self.lookup(&t));
+                match found {
+                    Ok((l, v)) => {
+                        current = l;
+                        value = v;
+                    },
+                    Err(e) => {
+                        self.lookups.pop();
+                        return Err(e);
+                    },
+                }
+            }
+            value =
+                    match patch::get(value, std::slice::from_ref(token)) {
+                        Some(v) => v,
+                        None => {
+                            self.lookups.pop();
+                            return Err(format!("dangling reference '{}'", Resolver::describe(location)));
+                        },
+                    };
+            current.1.push(token.clone());
+        } // for
+        self.lookups.pop();
+
+        Ok((current, value))
+    }
+
+    // Returns the resolved Value at a given location.
+    fn resolve_location(&mut self, location: Location) -> Result<Value, String> {
+        if let Some(v) = self.resolved.get(&location) {
+            return Ok(v.clone());
+        }
+        if let Some(pos) = self.stack.iter().position(|l| *l == location) {
+            return Err(Resolver::cycle(&self.stack[pos..], &location));
+        }
+
+        let ((doc, _), value) = self.lookup(&location)?;
+        self.stack.push(location);
+        let res = self.resolve_value(doc, value)?;
+        let location = self.stack.pop().unwrap();
+        self.resolved.insert(location, res.clone());
+
+        Ok(res)
+    }
+
+    // Returns a given Value of a given document whose references are resolved.
+    fn resolve_value(&mut self, doc: usize, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::String(s) => self.resolve_string(doc, s),
+            Value::Array(a) => {
+                let mut new_array = Vec::new();
+                for v in a.iter() {
+                    new_array.push(self.resolve_value(doc, v)?);
+                } // for
+
+                Ok(Value::Array(new_array))
+            },
+            Value::Object(o) => {
+                if let Some(location) = Resolver::reference(value, doc) {
+                    return self.resolve_location(location?);
+                }
+
+                let mut new_obj = IndexMap::new();
+                for (k, v) in o.iter() {
+                    new_obj.insert(k.clone(), self.resolve_value(doc, v)?);
+                } // for
+
+                Ok(Value::Object(new_obj))
+            },
+            _ => Ok(value.clone()),
+        }
+    }
+
+    // Returns a given string of a given document whose references are resolved.
+    // A string made of a single reference is replaced by the referenced Value, of any type.
+    fn resolve_string(&mut self, doc: usize, content: &str) -> Result<Value, String> {
+        let mut sb = String::new();
+        let mut pos = 0;
+        for (start, end, expr) in placeholders(content) {
+            let location =
+                    match parse_string_ref(expr, doc) {
+                        Some(l) => l?,
+                        // Not a reference, such as an environment variable.
+                        None => continue,
+                    };
+            let v = self.resolve_location(location)?;
+            if start == 0 && end == content.len() {
+                return Ok(v);
+            }
+
+            sb.push_str(&unescape(&content[pos..start]));
+            match v {
+                Value::Boolean(b) => sb.push_str(&b.to_string()),
+                Value::Integer(i) => sb.push_str(&i.to_string()),
+                Value::Float(f) => sb.push_str(&f.to_string()),
+                Value::String(s) => sb.push_str(&s),
+                _ => return Err(format!("'${{{}}}' is not a string, a number or a boolean", expr)),
+            }
+            pos = end;
+        } // for
+        sb.push_str(&unescape(&content[pos..]));
+
+        Ok(Value::String(sb))
+    }
+}
+
+// Creates new Values by resolving the references of given documents:
+// - '${.PATH}' and '${N.PATH}' within strings,
+// - '{"$ref": "#POINTER"}' and '{"$ref": "N#POINTER"}' objects,
+// where N is the index of another document.
+pub fn resolve_refs(docs: &[Value]) -> Result<Vec<Value>, String> {
+    let mut resolver = Resolver {
+        docs,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+        lookups: Vec::new(),
+    };
+    let mut res = Vec::new();
+    for idx in 0..docs.len() {
+        match resolver.resolve_location((idx, Vec::new())) {
+            Ok(v) => res.push(v),
+            Err(e) => return Err(format!("document {}: {}", idx, e)),
+        }
+    } // for
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod resolve_refs {
+        use super::*;
+
+        #[test]
+        fn it_resolves_string_refs() {
+            let doc = new_value(r#"{"service":{"name":"app","port":80},"host":"${.service.name}.local:${.service.port}","port":"${.service.port}","url":"http://${.host}","env":"${HOME}","price":"$${.x}"}"#);
+
+            let res = resolve_refs(&[doc]);
+            assert_eq!(res, Ok(vec![new_value(r#"{"service":{"name":"app","port":80},"host":"app.local:80","port":80,"url":"http://app.local:80","env":"${HOME}","price":"${.x}"}"#)]));
+        }
+
+        #[test]
+        fn it_resolves_object_refs() {
+            let docs = vec![
+                new_value(r##"{"definitions":{"port":{"type":"integer"}},"a":{"$ref":"#/definitions/port"},"b":{"$ref":"1#/version"},"c":"${1.version}"}"##),
+                new_value(r#"{"version":"1.2"}"#),
+            ];
+
+            let res = resolve_refs(&docs);
+            assert_eq!(res, Ok(vec![
+                new_value(r#"{"definitions":{"port":{"type":"integer"}},"a":{"type":"integer"},"b":"1.2","c":"1.2"}"#),
+                new_value(r#"{"version":"1.2"}"#),
+            ]));
+        }
+
+        #[test]
+        fn it_keeps_non_local_refs() {
+            let doc = new_value(r##"{"a":{"$ref":"other.json#/x"},"b":{"$ref":"https://example.com/schema.json"},"c":{"$ref":"#/d"},"d":1}"##);
+
+            let res = resolve_refs(&[doc]);
+            assert_eq!(res, Ok(vec![new_value(r##"{"a":{"$ref":"other.json#/x"},"b":{"$ref":"https://example.com/schema.json"},"c":1,"d":1}"##)]));
+        }
+
+        #[test]
+        fn it_follows_refs_along_paths() {
+            let doc = new_value(r##"{"a":{"$ref":"#/b"},"b":{"c":1},"d":"${.a.c}"}"##);
+
+            let res = resolve_refs(&[doc]);
+            assert_eq!(res, Ok(vec![new_value(r#"{"a":{"c":1},"b":{"c":1},"d":1}"#)]));
+        }
+
+        #[test]
+        fn it_resolves_shared_refs_once() {
+            // Each entry refers twice to the next one.
+            let mut o = IndexMap::new();
+            for idx in 0..40 {
+                o.insert(format!("a{}", idx), Value::String(format!("${{.a{}}}${{.a{}}}", idx + 1, idx + 1)));
+            } // for
+            o.insert("a40".to_owned(), Value::String(String::new()));
+
+            let res = resolve_refs(&[Value::Object(o)]).unwrap();
+            assert_eq!(patch::get(&res[0], &["a0".to_owned()]), Some(&Value::String(String::new())));
+        }
+
+        #[test]
+        fn it_detects_cycles() {
+            let res = resolve_refs(&[new_value(r#"{"a":"${.b}","b":{"c":"${.a}"}}"#)]);
+            assert_eq!(res, Err("document 0: reference cycle 0#/b -> 0#/a -> 0#/b".to_owned()));
+            let res = resolve_refs(&[new_value(r##"{"a":{"b":{"$ref":"#/a"}}}"##)]);
+            assert!(res.unwrap_err().contains("reference cycle"));
+        }
+
+        #[test]
+        fn it_fails_on_dangling_refs() {
+            let res = resolve_refs(&[new_value(r#"{"a":"${.b.c}","b":{}}"#)]);
+            assert_eq!(res, Err("document 0: dangling reference '0#/b/c'".to_owned()));
+            let res = resolve_refs(&[new_value(r##"{"a":{"$ref":"2#/b"}}"##)]);
+            assert_eq!(res, Err("document 0: dangling reference '2#/b': no document 2".to_owned()));
+        }
+    }
+}
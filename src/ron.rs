@@ -0,0 +1,557 @@
+use indexmap::IndexMap;
+use crate::value::Value;
+
+const INDENT: &str = "    ";
+
+// Tests whether a given name is a RON identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+// A RON document being read.
+struct Parser<'a> {
+    content: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.content[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, msg: &str) -> String {
+        let line = self.content[..self.pos].matches('\n').count() + 1;
+        format!("line {}: {}", line, msg)
+    }
+
+    // Skips the whitespaces and the comments, '// ...' and the nested '/* ... */'.
+    fn skip_blanks(&mut self) -> Result<(), String> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                let mut depth = 0;
+                loop {
+                    let rest = self.rest();
+                    if rest.starts_with("/*") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if rest.starts_with("*/") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if let Some(c) = rest.chars().next() {
+                        self.pos += c.len_utf8();
+                    } else {
+                        return Err(self.error("unterminated comment"));
+                    }
+                } // loop
+            } else {
+                return Ok(());
+            }
+        } // loop
+    }
+
+    // Consumes a given token, after the blanks.
+    fn eat(&mut self, token: char) -> Result<bool, String> {
+        self.skip_blanks()?;
+        if self.peek() == Some(token) {
+            self.pos += token.len_utf8();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, token: char) -> Result<(), String> {
+        if self.eat(token)? {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", token)))
+        }
+    }
+
+    // Reads an identifier, raw ones like 'r#type' included, if any.
+    fn read_identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        let start = if rest.starts_with("r#") { 2 } else { 0 };
+        let len = rest[start..].find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len() - start);
+        if is_identifier(&rest[start..start + len]) {
+            self.pos += start + len;
+            Some(&rest[start..start + len])
+        } else {
+            None
+        }
+    }
+
+    // Reads the elements ending with a given token, separated by commas, with an optional trailing comma.
+    fn read_elements<F>(&mut self, end: char, mut read_element: F) -> Result<(), String>
+    where
+        F: FnMut(&mut Self) -> Result<(), String>,
+    {
+        loop {
+            if self.eat(end)? {
+                return Ok(());
+            }
+            read_element(self)?;
+            if !self.eat(',')? {
+                return self.expect(end);
+            }
+        } // loop
+    }
+
+    // Reads the content of parentheses, after the opening one: a struct, a tuple or a unit.
+    fn read_parenthesized(&mut self) -> Result<Value, String> {
+        self.skip_blanks()?;
+        let start = self.pos;
+        let is_struct = self.read_identifier().is_some() && self.eat(':')?;
+        self.pos = start;
+
+        if is_struct {
+            let mut new_obj = IndexMap::new();
+            self.read_elements(')', |p| {
+                p.skip_blanks()?;
+                let name =
+                        match p.read_identifier() {
+                            Some(n) => n.to_owned(),
+                            None => return Err(p.error("expected a field name")),
+                        };
+                p.expect(':')?;
+                new_obj.insert(name, p.read_value()?);
+                Ok(())
+            })?;
+            Ok(Value::Object(new_obj))
+        } else {
+            let mut new_array = Vec::new();
+            self.read_elements(')', |p| {
+                new_array.push(p.read_value()?);
+                Ok(())
+            })?;
+            if new_array.is_empty() {
+                Ok(Value::Nil)
+            } else {
+                Ok(Value::Array(new_array))
+            }
+        }
+    }
+
+    // Reads a string, after its opening quote, or a character.
+    fn read_quoted(&mut self, quote: char) -> Result<String, String> {
+        let mut sb = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((idx, c)) = chars.next() {
+            if c == quote {
+                self.pos += idx + 1;
+                return Ok(sb);
+            }
+            if c != '\\' {
+                sb.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some((_, 'n')) => sb.push('\n'),
+                Some((_, 'r')) => sb.push('\r'),
+                Some((_, 't')) => sb.push('\t'),
+                Some((_, '0')) => sb.push('\0'),
+                Some((_, e @ ('\\' | '"' | '\''))) => sb.push(e),
+                Some((start, e @ ('x' | 'u'))) => {
+                    // '\xHH' for an ASCII character, '\u{H}' for 1 to 6 hex digits.
+                    let rest = &self.rest()[start + 1..];
+                    let (hex, len) =
+                            if e == 'x' {
+                                (rest.get(..2).unwrap_or(""), 2)
+                            } else {
+                                let digits = rest.strip_prefix('{')
+                                    .map(|r| &r[..r.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(r.len())])
+                                    .filter(|d| rest[d.len() + 1..].starts_with('}'))
+                                    .unwrap_or("");
+                                (digits, digits.len() + 2)
+                            };
+                    let is_hex = (1..=6).contains(&hex.len()) && hex.bytes().all(|b| b.is_ascii_hexdigit());
+                    match u32::from_str_radix(hex, 16).ok().filter(|_| is_hex).and_then(char::from_u32) {
+                        Some(u) if e == 'u' || u.is_ascii() => sb.push(u),
+                        _ => {
+                            self.pos += start;
+                            return Err(self.error("wrong escape"));
+                        },
+                    }
+                    chars.by_ref().take(len).for_each(drop);
+                },
+                // A line continuation.
+                Some((_, '\n')) => {
+                    while let Some((_, ' ' | '\t' | '\r' | '\n')) = chars.clone().next() {
+                        chars.next();
+                    } // while
+                },
+                _ => return Err(self.error("wrong escape")),
+            }
+        } // while
+
+        Err(self.error("unterminated string"))
+    }
+
+    // Reads a raw string, like r#"..."#, after its 'r'.
+    fn read_raw_string(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        let end = format!("\"{}", "#".repeat(hashes));
+        let content = &rest[hashes + 1..];
+        match content.find(&end) {
+            Some(idx) => {
+                self.pos += hashes + 1 + idx + end.len();
+                Ok(content[..idx].to_owned())
+            },
+            None => Err(self.error("unterminated string")),
+        }
+    }
+
+    // Reads a number: an integer, decimal or not, or a float.
+    fn read_number(&mut self) -> Result<Value, String> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !c.is_ascii_alphanumeric() && !"+-._".contains(c)).unwrap_or(rest.len());
+        // The signs are part of the number at its start, or after an exponent only.
+        let len = rest[..len].char_indices()
+            .skip(1)
+            .find(|(idx, c)| "+-".contains(*c) && !rest[..*idx].ends_with(['e', 'E']))
+            .map_or(len, |(idx, _)| idx);
+        let token = &rest[..len];
+        let digits = token.replace('_', "");
+        let (sign, unsigned) =
+                match digits.strip_prefix('-') {
+                    Some(u) => ("-", u),
+                    None => ("", digits.strip_prefix('+').unwrap_or(&digits)),
+                };
+
+        let radix =
+                match unsigned.get(..2) {
+                    Some("0x") => 16,
+                    Some("0b") => 2,
+                    Some("0o") => 8,
+                    _ => 10,
+                };
+        let res =
+                if radix != 10 {
+                    i64::from_str_radix(&format!("{}{}", sign, &unsigned[2..]), radix).ok().map(Value::Integer)
+                } else if let Ok(i) = digits.parse::<i64>() {
+                    Some(Value::Integer(i))
+                } else {
+                    // An integer overflowing i64 is kept as a Float.
+                    match unsigned {
+                        "inf" => Some(Value::Float(if sign == "-" { f64::NEG_INFINITY } else { f64::INFINITY })),
+                        "NaN" => Some(Value::Float(f64::NAN)),
+                        _ if unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') => digits.parse::<f64>().ok().map(Value::Float),
+                        _ => None,
+                    }
+                };
+
+        match res {
+            Some(v) => {
+                self.pos += len;
+                Ok(v)
+            },
+            None => Err(self.error(&format!("wrong number '{}'", token))),
+        }
+    }
+
+    // Reads the key of a map entry, as a string.
+    fn read_key(&mut self) -> Result<String, String> {
+        match self.read_value()? {
+            Value::String(s) => Ok(s),
+            Value::Nil => Ok("null".to_owned()),
+            Value::Boolean(b) => Ok(b.to_string()),
+            Value::Integer(i) => Ok(i.to_string()),
+            Value::Float(f) => Ok(f.to_string()),
+            _ => Err(self.error("wrong map key")),
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Value, String> {
+        self.skip_blanks()?;
+        let c =
+                match self.peek() {
+                    Some(c) => c,
+                    None => return Err(self.error("unexpected end")),
+                };
+        match c {
+            '"' | '\'' => {
+                self.pos += 1;
+                Ok(Value::String(self.read_quoted(c)?))
+            },
+            // Unlike a raw identifier, like r#type.
+            'r' if self.rest()[1..].trim_start_matches('#').starts_with('"') => {
+                self.pos += 1;
+                Ok(Value::String(self.read_raw_string()?))
+            },
+            '[' => {
+                self.pos += 1;
+                let mut new_array = Vec::new();
+                self.read_elements(']', |p| {
+                    new_array.push(p.read_value()?);
+                    Ok(())
+                })?;
+                Ok(Value::Array(new_array))
+            },
+            '{' => {
+                self.pos += 1;
+                let mut new_obj = IndexMap::new();
+                self.read_elements('}', |p| {
+                    let key = p.read_key()?;
+                    p.expect(':')?;
+                    new_obj.insert(key, p.read_value()?);
+                    Ok(())
+                })?;
+                Ok(Value::Object(new_obj))
+            },
+            '(' => {
+                self.pos += 1;
+                self.read_parenthesized()
+            },
+            '0'..='9' | '+' | '-' | '.' => self.read_number(),
+            _ => {
+                let name =
+                        match self.read_identifier() {
+                            Some(n) => n,
+                            None => return Err(self.error(&format!("unexpected '{}'", c))),
+                        };
+                match name {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    "None" => Ok(Value::Nil),
+                    "inf" => Ok(Value::Float(f64::INFINITY)),
+                    "NaN" => Ok(Value::Float(f64::NAN)),
+                    "Some" => {
+                        self.expect('(')?;
+                        let value = self.read_value()?;
+                        self.eat(',')?;
+                        self.expect(')')?;
+                        Ok(value)
+                    },
+                    // A named struct, tuple struct or enum variant.
+                    _ if self.eat('(')? => self.read_parenthesized(),
+                    // A unit variant.
+                    _ => Ok(Value::String(name.to_owned())),
+                }
+            },
+        }
+    }
+}
+
+// Reads a RON document:
+// - the structs, named or not, and the maps are Objects, whose map keys are strings,
+// - the lists, the tuples and the tuple structs or variants are Arrays,
+// - None and () are Nil, and Some(x) is x,
+// - the unit variants, like Red, are their names, and the characters strings.
+// The names of the structs and variants are dropped.
+pub fn from_str(content: &str) -> Result<Value, String> {
+    let mut parser = Parser { content, pos: 0 };
+    // The extensions, like '#![enable(implicit_some)]', are ignored.
+    loop {
+        parser.skip_blanks()?;
+        if !parser.rest().starts_with("#![") {
+            break;
+        }
+        match parser.rest().find(']') {
+            Some(idx) => parser.pos += idx + 1,
+            None => return Err(parser.error("unterminated attribute")),
+        }
+    } // loop
+
+    let value = parser.read_value()?;
+    parser.skip_blanks()?;
+    if parser.pos < content.len() {
+        return Err(parser.error("trailing characters"));
+    }
+
+    Ok(value)
+}
+
+// Returns a given string as a RON string.
+fn quote(s: &str) -> String {
+    let mut sb = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => sb.push_str("\\\""),
+            '\\' => sb.push_str("\\\\"),
+            '\n' => sb.push_str("\\n"),
+            '\r' => sb.push_str("\\r"),
+            '\t' => sb.push_str("\\t"),
+            _ if c.is_control() => sb.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            _ => sb.push(c),
+        }
+    } // for
+    sb.push('"');
+
+    sb
+}
+
+// Writes a given Value, whose nested lines have a given indentation when pretty.
+fn write_value(value: &Value, pretty: bool, indent: &str, sb: &mut String) {
+    match value {
+        Value::Nil => sb.push_str("None"),
+        Value::Boolean(b) => sb.push_str(&b.to_string()),
+        Value::Integer(i) => sb.push_str(&i.to_string()),
+        // The floats keep their decimal point, as 1.0, to be read back as such.
+        Value::Float(f) => sb.push_str(&format!("{:?}", f)),
+        Value::String(s) => sb.push_str(&quote(s)),
+        Value::Array(a) => {
+            let elements: Vec<(Option<String>, &Value)> = a.iter().map(|v| (None, v)).collect();
+            write_elements(&elements, ('[', ']'), pretty, indent, sb);
+        },
+        Value::Object(o) => {
+            // The objects whose keys are identifiers are structs.
+            let (delimiters, separator) =
+                    if !o.is_empty() && o.keys().all(|k| is_identifier(k)) {
+                        (('(', ')'), ":")
+                    } else {
+                        (('{', '}'), "")
+                    };
+            let elements: Vec<(Option<String>, &Value)> = o.iter()
+                .map(|(k, v)| {
+                    let key = if separator.is_empty() { quote(k) } else { k.clone() };
+                    (Some(key), v)
+                })
+                .collect();
+            write_elements(&elements, delimiters, pretty, indent, sb);
+        },
+    }
+}
+
+// Writes the elements of a list, a map or a struct, one per line with a trailing comma when pretty.
+fn write_elements(elements: &[(Option<String>, &Value)], delimiters: (char, char), pretty: bool, indent: &str, sb: &mut String) {
+    sb.push(delimiters.0);
+    let inner_indent = format!("{}{}", indent, INDENT);
+    for (idx, (key, v)) in elements.iter().enumerate() {
+        if pretty {
+            sb.push('\n');
+            sb.push_str(&inner_indent);
+        } else if idx > 0 {
+            sb.push(',');
+        }
+        if let Some(k) = key {
+            sb.push_str(k);
+            sb.push_str(if pretty { ": " } else { ":" });
+        }
+        write_value(v, pretty, &inner_indent, sb);
+        if pretty {
+            sb.push(',');
+        }
+    } // for
+    if pretty && !elements.is_empty() {
+        sb.push('\n');
+        sb.push_str(indent);
+    }
+    sb.push(delimiters.1);
+}
+
+// Writes a Value as a RON document, compact or pretty.
+// The Objects whose keys are identifiers are anonymous structs, like (name: "app"), the other ones maps,
+// and Nil is None.
+pub fn to_string(value: &Value, pretty: bool) -> String {
+    let mut sb = String::new();
+    write_value(value, pretty, "", &mut sb);
+
+    sb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn it_reads_values() {
+            let content = r##"#![enable(implicit_some)]
+// The configuration.
+Config(
+    name: "app\t\u{e9}", /* nested /* comment */ */
+    port: 8_080,
+    mask: 0xFF,
+    ratio: -1.5e1,
+    tags: ["a", r#"b"c"#, 'd'],
+    origin: Point(1, 2),
+    pair: (true, ()),
+    color: Red,
+    limit: Some(10),
+    next: None,
+    env: {"a b": 1, 2: Empty(),},
+)
+"##;
+
+            let res = from_str(content);
+            assert_eq!(res, Ok(new_value(r#"{"name":"app\té","port":8080,"mask":255,"ratio":-15.0,"tags":["a","b\"c","d"],"origin":[1,2],"pair":[true,null],"color":"Red","limit":10,"next":null,"env":{"a b":1,"2":null}}"#)));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_documents() {
+            assert_eq!(from_str("(a: 1,\n b: [1 2])"), Err("line 2: expected ']'".to_owned()));
+            assert_eq!(from_str("\"a"), Err("line 1: unterminated string".to_owned()));
+            assert_eq!(from_str("1 2"), Err("line 1: trailing characters".to_owned()));
+            assert_eq!(from_str("{[1]: 2}"), Err("line 1: wrong map key".to_owned()));
+        }
+
+        #[test]
+        fn it_reads_escapes() {
+            let res = from_str(r#"["\x41\u{1F600}\u{e9}\"\\\'\0", "a\
+                b", '\n']"#);
+            assert_eq!(res, Ok(new_value(r#"["A😀é\"\\'\u0000","ab","\n"]"#)));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_escapes() {
+            for content in [r#""\u00e9""#, r#""\u{}""#, r#""\u{1234567}""#, r#""\u{e9" }"#, r#""\x+1""#, r#""\xFF""#, r#""\q""#].iter() {
+                assert_eq!(from_str(content), Err("line 1: wrong escape".to_owned()), "{}", content);
+            } // for
+        }
+
+        #[test]
+        fn it_reads_raw_strings() {
+            let res = from_str(r###"[r"a\n", r#"b"c"#, r##"d"#e"##, r""]"###);
+            assert_eq!(res, Ok(new_value(r##"["a\\n","b\"c","d\"#e",""]"##)));
+            assert_eq!(from_str(r##"r#"a""##), Err("line 1: unterminated string".to_owned()));
+        }
+
+        #[test]
+        fn it_drops_struct_names() {
+            let res = from_str("Config(point: Point(x: 1, y: 2), unit: Unit, tuple: Pair(1, 2), empty: Empty())");
+            assert_eq!(res, Ok(new_value(r#"{"point":{"x":1,"y":2},"unit":"Unit","tuple":[1,2],"empty":null}"#)));
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_writes_compact_documents() {
+            let doc = new_value(r#"{"name":"a\"b","port":80,"ratio":1.0,"tags":["x",null],"env":{"a b":true},"empty":{}}"#);
+
+            let res = to_string(&doc, false);
+            assert_eq!(res, r#"(name:"a\"b",port:80,ratio:1.0,tags:["x",None],env:{"a b":true},empty:{})"#);
+            assert_eq!(from_str(&res), Ok(doc));
+        }
+
+        #[test]
+        fn it_writes_pretty_documents() {
+            let doc = new_value(r#"{"name":"app","ports":[80,443],"env":{}}"#);
+
+            let res = to_string(&doc, true);
+            assert_eq!(res, "(\n    name: \"app\",\n    ports: [\n        80,\n        443,\n    ],\n    env: {},\n)");
+            assert_eq!(from_str(&res), Ok(doc));
+        }
+    }
+}
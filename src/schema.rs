@@ -0,0 +1,319 @@
+use indexmap::{IndexMap, IndexSet};
+use jsonschema::Validator;
+use crate::value::Value;
+
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// The maximum number of distinct strings of an inferred enum.
+const ENUM_LIMIT: usize = 5;
+
+// A JSON schema, of draft 2020-12 unless the schema states another draft with '$schema'.
+pub struct Schema {
+    // The schema as a document.
+    document: Value,
+    validator: Validator,
+}
+
+// A violation of a schema.
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    // The JSON pointer of the invalid value.
+    pub instance_path: String,
+    // The JSON pointer of the failing schema keyword.
+    pub schema_path: String,
+    pub message: String,
+}
+
+impl Schema {
+    pub fn new(document: Value) -> Result<Self, String> {
+        let json = serde_json::to_value(&document).unwrap();
+        match jsonschema::validator_for(&json) {
+            Ok(validator) => Ok(Schema {
+                document,
+                validator,
+            }),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // Returns every violation of the schema by a given document.
+    pub fn validate(&self, doc: &Value) -> Vec<Violation> {
+        let instance = serde_json::to_value(doc).unwrap();
+        self.validator.iter_errors(&instance)
+            .map(|e| Violation {
+                instance_path: e.instance_path.to_string(),
+                schema_path: e.schema_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect()
+    }
+
+    // Creates a new Value by filling a given document with the 'default' values of the schema.
+    // The defaults of the 'properties' and 'items' subschemas are filled, '$ref' is not followed.
+    pub fn fill_defaults(&self, doc: &Value) -> Value {
+        let mut res = doc.clone();
+        fill_defaults(&self.document, &mut res);
+
+        res
+    }
+}
+
+fn fill_defaults(schema: &Value, doc: &mut Value) {
+    let schema =
+            match schema {
+                Value::Object(o) => o,
+                _ => return,
+            };
+
+    match doc {
+        Value::Object(o) => {
+            if let Some(Value::Object(properties)) = schema.get("properties") {
+                for (key, subschema) in properties.iter() {
+                    if !o.contains_key(key) {
+                        if let Value::Object(s) = subschema {
+                            if let Some(default) = s.get("default") {
+                                o.insert(key.clone(), default.clone());
+                            }
+                        }
+                    }
+                    if let Some(v) = o.get_mut(key) {
+                        fill_defaults(subschema, v);
+                    }
+                } // for
+            }
+        },
+        Value::Array(a) => {
+            if let Some(items) = schema.get("items") {
+                for v in a.iter_mut() {
+                    fill_defaults(items, v);
+                } // for
+            }
+        },
+        _ => {},
+    }
+}
+
+// The values seen at the same location of sample documents.
+#[derive(Default)]
+struct Shape {
+    nil: bool,
+    boolean: bool,
+    integer: bool,
+    float: bool,
+    // The number of strings, and the distinct strings up to the enum limit.
+    string_count: usize,
+    strings: IndexSet<String>,
+    // The shape of the elements of every array.
+    array: Option<Box<Shape>>,
+    // The number of objects, and the shapes and numbers of their entries.
+    object_count: usize,
+    entries: IndexMap<String, (Shape, usize)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Nil => self.nil = true,
+            Value::Boolean(_) => self.boolean = true,
+            Value::Integer(_) => self.integer = true,
+            Value::Float(_) => self.float = true,
+            Value::String(s) => {
+                self.string_count += 1;
+                if self.strings.len() <= ENUM_LIMIT {
+                    self.strings.insert(s.clone());
+                }
+            },
+            Value::Array(a) => {
+                let items = self.array.get_or_insert_with(Box::default);
+                for v in a.iter() {
+                    items.add(v);
+                } // for
+            },
+            Value::Object(o) => {
+                self.object_count += 1;
+                for (key, v) in o.iter() {
+                    let entry = self.entries.entry(key.clone()).or_insert_with(|| (Shape::default(), 0));
+                    entry.0.add(v);
+                    entry.1 += 1;
+                } // for
+            },
+        }
+    }
+
+    // Creates the schema of the shape.
+    fn to_schema(&self) -> Value {
+        let mut types = Vec::new();
+        if self.nil {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.float {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.string_count > 0 {
+            types.push("string");
+        }
+        if self.array.is_some() {
+            types.push("array");
+        }
+        if self.object_count > 0 {
+            types.push("object");
+        }
+
+        let mut schema = IndexMap::new();
+        match types.len() {
+            0 => {},
+            1 => {
+                schema.insert("type".to_owned(), Value::String(types[0].to_owned()));
+            },
+            _ => {
+                let types = types.iter().map(|t| Value::String((*t).to_owned())).collect();
+                schema.insert("type".to_owned(), Value::Array(types));
+            },
+        }
+
+        // Strings are an enum when they are the only non-null values and some of them repeat.
+        let only_strings = types.iter().all(|t| *t == "string" || *t == "null");
+        if only_strings && self.strings.len() <= ENUM_LIMIT && self.string_count > self.strings.len() {
+            let mut values: Vec<Value> = self.strings.iter().map(|s| Value::String(s.clone())).collect();
+            if self.nil {
+                values.push(Value::Nil);
+            }
+            schema.insert("enum".to_owned(), Value::Array(values));
+        }
+
+        if let Some(items) = &self.array {
+            let items = items.to_schema();
+            if let Value::Object(o) = &items {
+                if !o.is_empty() {
+                    schema.insert("items".to_owned(), items);
+                }
+            }
+        }
+
+        if self.object_count > 0 {
+            let mut properties = IndexMap::new();
+            let mut required = Vec::new();
+            for (key, (shape, count)) in self.entries.iter() {
+                properties.insert(key.clone(), shape.to_schema());
+                if *count == self.object_count {
+                    required.push(Value::String(key.clone()));
+                }
+            } // for
+            schema.insert("properties".to_owned(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_owned(), Value::Array(required));
+            }
+        }
+
+        Value::Object(schema)
+    }
+}
+
+// Creates a JSON schema matching given sample documents.
+// The keys present in every sample object are required, and low-cardinality strings are enums.
+pub fn infer_schema(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples.iter() {
+        shape.add(sample);
+    } // for
+
+    let mut schema = IndexMap::new();
+    schema.insert("$schema".to_owned(), Value::String(SCHEMA_DRAFT.to_owned()));
+    if let Value::Object(o) = shape.to_schema() {
+        schema.extend(o);
+    }
+
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    const SCHEMA: &str = r#"{
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": {"type": "string"},
+            "replicas": {"type": "integer", "minimum": 1, "default": 1},
+            "ports": {"type": "array", "items": {"type": "object", "properties": {"protocol": {"default": "TCP"}}}}
+        }
+    }"#;
+
+    mod validate {
+        use super::*;
+
+        #[test]
+        fn it_lists_violations() {
+            let schema = Schema::new(new_value(SCHEMA)).unwrap();
+
+            assert!(schema.validate(&new_value(r#"{"name":"app","replicas":2}"#)).is_empty());
+            let violations = schema.validate(&new_value(r#"{"replicas":0,"ports":3}"#));
+            let paths: Vec<(&str, &str)> = violations.iter().map(|v| (v.instance_path.as_str(), v.schema_path.as_str())).collect();
+            assert_eq!(paths.len(), 3);
+            assert!(paths.contains(&("", "/required")));
+            assert!(paths.contains(&("/replicas", "/properties/replicas/minimum")));
+            assert!(paths.contains(&("/ports", "/properties/ports/type")));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_schemas() {
+            assert!(Schema::new(new_value(r#"{"type":"wrong"}"#)).is_err());
+        }
+    }
+
+    mod fill_defaults {
+        use super::*;
+
+        #[test]
+        fn it_fills_defaults() {
+            let schema = Schema::new(new_value(SCHEMA)).unwrap();
+
+            let res = schema.fill_defaults(&new_value(r#"{"name":"app","ports":[{"port":80},{"port":53,"protocol":"UDP"}]}"#));
+            assert_eq!(res, new_value(r#"{"name":"app","replicas":1,"ports":[{"port":80,"protocol":"TCP"},{"port":53,"protocol":"UDP"}]}"#));
+        }
+    }
+
+    mod infer_schema {
+        use super::*;
+
+        #[test]
+        fn it_infers_schemas() {
+            let samples = vec![
+                new_value(r#"{"name":"app","tier":"web","replicas":2,"ports":[80,443],"owner":null}"#),
+                new_value(r#"{"name":"db","tier":"data","replicas":1.5,"ports":[]}"#),
+                new_value(r#"{"name":"cache","tier":"data","owner":"ops"}"#),
+            ];
+
+            let schema = infer_schema(&samples);
+            assert_eq!(serde_json::to_string(&schema).unwrap(), concat!(
+                r#"{"$schema":"https://json-schema.org/draft/2020-12/schema","type":"object","properties":{"#,
+                r#""name":{"type":"string"},"#,
+                r#""tier":{"type":"string","enum":["web","data"]},"#,
+                r#""replicas":{"type":"number"},"#,
+                r#""ports":{"type":"array","items":{"type":"integer"}},"#,
+                r#""owner":{"type":["null","string"]}},"#,
+                r#""required":["name","tier"]}"#,
+            ));
+        }
+
+        #[test]
+        fn it_validates_samples() {
+            let samples = vec![
+                new_value(r#"{"a":[{"b":1},{"b":"x","c":true}]}"#),
+                new_value(r#"{"a":[],"d":null}"#),
+            ];
+
+            let schema = Schema::new(infer_schema(&samples)).unwrap();
+            for sample in samples.iter() {
+                assert!(schema.validate(sample).is_empty());
+            } // for
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::value::Value;
+
+// The styles of the written tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    // Aligned ASCII, with borders.
+    Ascii,
+    Html,
+    // GitHub flavored Markdown.
+    Markdown,
+}
+
+// Returns the text of a given cell: the scalars as is, and the Arrays and Objects as JSON.
+fn cell_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Nil) => String::new(),
+        Some(Value::Boolean(b)) => b.to_string(),
+        Some(Value::Integer(i)) => i.to_string(),
+        Some(Value::Float(f)) => f.to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => serde_json::to_string(v).unwrap(),
+    }
+}
+
+// Returns the columns and the cell texts of a given Array of Objects.
+// Without given columns, they are the keys of the Objects, in the order they are first seen.
+fn rows(value: &Value, columns: Option<&[String]>) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let a =
+            match value {
+                Value::Array(a) => a,
+                _ => return Err("table requires an array of objects".to_owned()),
+            };
+    let mut objects = Vec::new();
+    for (idx, v) in a.iter().enumerate() {
+        match v {
+            Value::Object(o) => objects.push(o),
+            _ => return Err(format!("row {}: not an object", idx + 1)),
+        }
+    } // for
+
+    let columns =
+            match columns {
+                Some(c) => c.to_vec(),
+                None => {
+                    let mut res: Vec<String> = Vec::new();
+                    for o in objects.iter() {
+                        for k in o.keys() {
+                            if !res.contains(k) {
+                                res.push(k.clone());
+                            }
+                        } // for
+                    } // for
+                    res
+                },
+            };
+    let cells = objects.iter()
+        .map(|o| columns.iter().map(|c| cell_text(o.get(c))).collect())
+        .collect();
+
+    Ok((columns, cells))
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+fn to_ascii(columns: &[String], cells: &[Vec<String>]) -> String {
+    let cells: Vec<Vec<String>> = cells.iter()
+        .map(|r| r.iter().map(|c| c.replace('\n', "\\n")).collect())
+        .collect();
+    let widths: Vec<usize> = columns.iter().enumerate()
+        .map(|(idx, c)| cells.iter().map(|r| width(&r[idx])).chain(std::iter::once(width(c))).max().unwrap())
+        .collect();
+    let border: String = widths.iter().map(|w| format!("+{}", "-".repeat(w + 2))).collect::<String>() + "+\n";
+    let line = |row: &[String]| -> String {
+        row.iter().zip(widths.iter())
+            .map(|(c, w)| format!("| {}{} ", c, " ".repeat(w - width(c))))
+            .collect::<String>() + "|\n"
+    };
+
+    let mut sb = border.clone();
+    sb.push_str(&line(columns));
+    sb.push_str(&border);
+    for r in cells.iter() {
+        sb.push_str(&line(r));
+    } // for
+    if !cells.is_empty() {
+        sb.push_str(&border);
+    }
+
+    sb
+}
+
+fn to_html(columns: &[String], cells: &[Vec<String>]) -> String {
+    let line = |row: &[String], tag: &str| -> String {
+        let cells: String = row.iter().map(|c| format!("<{}>{}</{}>", tag, escape_html(c), tag)).collect();
+        format!("    <tr>{}</tr>\n", cells)
+    };
+
+    let mut sb = String::from("<table>\n  <thead>\n");
+    sb.push_str(&line(columns, "th"));
+    sb.push_str("  </thead>\n  <tbody>\n");
+    for r in cells.iter() {
+        sb.push_str(&line(r, "td"));
+    } // for
+    sb.push_str("  </tbody>\n</table>\n");
+
+    sb
+}
+
+fn to_markdown(columns: &[String], cells: &[Vec<String>]) -> String {
+    let escape = |c: &str| c.replace('|', "\\|").replace('\n', "<br>");
+    let columns: Vec<String> = columns.iter().map(|c| escape(c)).collect();
+    let cells: Vec<Vec<String>> = cells.iter()
+        .map(|r| r.iter().map(|c| escape(c)).collect())
+        .collect();
+    // The delimiter row needs 3 dashes at least.
+    let widths: Vec<usize> = columns.iter().enumerate()
+        .map(|(idx, c)| cells.iter().map(|r| width(&r[idx])).chain([width(c), 3]).max().unwrap())
+        .collect();
+    let line = |row: &[String]| -> String {
+        row.iter().zip(widths.iter())
+            .map(|(c, w)| format!("| {}{} ", c, " ".repeat(w - width(c))))
+            .collect::<String>() + "|\n"
+    };
+
+    let mut sb = line(&columns);
+    sb.push_str(&widths.iter().map(|w| format!("| {} ", "-".repeat(*w))).collect::<String>());
+    sb.push_str("|\n");
+    for r in cells.iter() {
+        sb.push_str(&line(r));
+    } // for
+
+    sb
+}
+
+// Writes an Array of Objects as a table, one row per Object.
+// The given columns select and order the columns, otherwise every key is a column.
+pub fn to_string(value: &Value, style: TableStyle, columns: Option<&[String]>) -> Result<String, String> {
+    let (columns, cells) = rows(value, columns)?;
+    if columns.is_empty() {
+        return Err("table without columns".to_owned());
+    }
+
+    let res =
+            match style {
+                TableStyle::Ascii => to_ascii(&columns, &cells),
+                TableStyle::Html => to_html(&columns, &cells),
+                TableStyle::Markdown => to_markdown(&columns, &cells),
+            };
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::new_value;
+
+    const DOC: &str = r#"[{"name":"serde","version":"1.0"},{"name":"a|b<c>","deps":["x"],"optional":true}]"#;
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn it_writes_ascii_tables() {
+            let res = to_string(&new_value(DOC), TableStyle::Ascii, None);
+            assert_eq!(res, Ok(concat!(
+                "+--------+---------+-------+----------+\n",
+                "| name   | version | deps  | optional |\n",
+                "+--------+---------+-------+----------+\n",
+                "| serde  | 1.0     |       |          |\n",
+                "| a|b<c> |         | [\"x\"] | true     |\n",
+                "+--------+---------+-------+----------+\n",
+            ).to_owned()));
+        }
+
+        #[test]
+        fn it_writes_markdown_tables() {
+            let columns = ["optional".to_owned(), "name".to_owned()];
+
+            let res = to_string(&new_value(DOC), TableStyle::Markdown, Some(&columns));
+            assert_eq!(res, Ok(concat!(
+                "| optional | name    |\n",
+                "| -------- | ------- |\n",
+                "|          | serde   |\n",
+                "| true     | a\\|b<c> |\n",
+            ).to_owned()));
+        }
+
+        #[test]
+        fn it_writes_html_tables() {
+            let columns = ["name".to_owned()];
+
+            let res = to_string(&new_value(DOC), TableStyle::Html, Some(&columns));
+            assert_eq!(res, Ok(concat!(
+                "<table>\n",
+                "  <thead>\n",
+                "    <tr><th>name</th></tr>\n",
+                "  </thead>\n",
+                "  <tbody>\n",
+                "    <tr><td>serde</td></tr>\n",
+                "    <tr><td>a|b&lt;c&gt;</td></tr>\n",
+                "  </tbody>\n",
+                "</table>\n",
+            ).to_owned()));
+        }
+
+        #[test]
+        fn it_fails_on_wrong_documents() {
+            assert_eq!(to_string(&new_value(r#"{"a":1}"#), TableStyle::Ascii, None), Err("table requires an array of objects".to_owned()));
+            assert_eq!(to_string(&new_value(r#"[{"a":1},2]"#), TableStyle::Ascii, None), Err("row 2: not an object".to_owned()));
+            assert_eq!(to_string(&new_value("[]"), TableStyle::Ascii, None), Err("table without columns".to_owned()));
+        }
+    }
+}
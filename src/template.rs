@@ -1,11 +1,15 @@
+use sha2::{Digest, Sha256};
 use std::collections::VecDeque;
 use std::iter::FromIterator;
+use crate::command;
+use crate::value;
 
+// The tokens with the template line they start at.
 #[derive(PartialEq, Eq, Clone)]
 enum Token {
-    Expression(String),
-    Statement(String),
-    Text(String),
+    Expression(String, usize),
+    Statement(String, usize),
+    Text(String, usize),
 }
 
 #[derive(PartialEq, Eq)]
@@ -19,11 +23,27 @@ enum Context {
 
 // Performs a lexical analysis on a given template.
 fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
+    // The line number of each character.
+    let mut lines = Vec::new();
+    let mut line = 1;
+    for c in content.chars() {
+        lines.push(line);
+        if c == '\n' {
+            line += 1;
+        }
+    } // for
+    lines.push(line);
+    let total = lines.len() - 1;
+    // Returns the line number of the next character to read.
+    let line_at = |remaining: usize| lines[total - remaining];
+
     let mut content = VecDeque::from_iter(content.chars());
     let mut tokens = VecDeque::new();
 
     let mut context = Context::Text;
     let mut buf = String::new();
+    // The line the current token starts at.
+    let mut start = 1;
     loop {
         let curr =
                 match content.pop_front() {
@@ -33,12 +53,12 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                             Context::Comment | Context::Expression | Context::Statement => return Err(()),
                             Context::StatementLine => {
                                 if !buf.is_empty() {
-                                    tokens.push_back(Token::Statement(buf.trim().to_owned()));
+                                    tokens.push_back(Token::Statement(buf.trim().to_owned(), start));
                                 }
                             },
                             Context::Text => {
                                 if !buf.is_empty() {
-                                    tokens.push_back(Token::Text(buf.clone()));
+                                    tokens.push_back(Token::Text(buf.clone(), start));
                                 }
                             },
                         } // context
@@ -61,7 +81,7 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                                         if buf.ends_with("-") {
                                             // Remove whitespaces until the next newline.
                                             let mut cnt = 0;
-                                            for (_, c) in content.iter().enumerate() {
+                                            for c in content.iter() {
                                                 if *c == ' ' || *c == '\t' {
                                                     cnt += 1;
                                                     continue;
@@ -84,14 +104,14 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                                         }
                                     },
                                     Context::Expression => {
-                                        tokens.push_back(Token::Expression(buf.trim().to_owned()));
+                                        tokens.push_back(Token::Expression(buf.trim().to_owned(), start));
                                     },
                                     Context::Statement => {
                                         if buf.ends_with("-") {
                                             buf.pop();
                                             // Remove whitespaces until the next newline.
                                             let mut cnt = 0;
-                                            for (_, c) in content.iter().enumerate() {
+                                            for c in content.iter() {
                                                 if *c == ' ' || *c == '\t' {
                                                     cnt += 1;
                                                     continue;
@@ -112,13 +132,14 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                                                 cnt -= 1;
                                             } // loop
                                         }
-                                        tokens.push_back(Token::Statement(buf.trim().to_owned()));
+                                        tokens.push_back(Token::Statement(buf.trim().to_owned(), start));
                                     },
                                     _ => panic!("wtf"),
                                 } // match context
                                 buf.clear();
                             }
                             context = Context::Text;
+                            start = line_at(content.len());
                         },
                         Some('%') => {
                             // '%%'
@@ -137,9 +158,10 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                 if curr == '\n' {
                     // '\n'
                     // Change of mode -> text.
-                    tokens.push_back(Token::Statement(buf.trim().to_owned()));
+                    tokens.push_back(Token::Statement(buf.trim().to_owned(), start));
                     buf.clear();
                     context = Context::Text;
+                    start = line_at(content.len());
                 } else {
                     buf.push(curr);
                 }
@@ -162,19 +184,21 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                                     // '<%#'
                                     // Change of mode -> comment.
                                     if !buf.is_empty() {
-                                        tokens.push_back(Token::Text(buf.clone()));
+                                        tokens.push_back(Token::Text(buf.clone(), start));
                                         buf.clear();
                                     }
                                     context = Context::Comment;
+                                    start = line_at(content.len());
                                 },
                                 Some('=') => {
                                     // '<%='
                                     // Change of mode -> expression.
                                     if !buf.is_empty() {
-                                        tokens.push_back(Token::Text(buf.clone()));
+                                        tokens.push_back(Token::Text(buf.clone(), start));
                                         buf.clear();
                                     }
                                     context = Context::Expression;
+                                    start = line_at(content.len());
                                 },
                                 Some('-') => {
                                     // '<%-'
@@ -209,19 +233,21 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                                             buf.pop();
                                             cnt -= 1;
                                         } // loop
-                                        tokens.push_back(Token::Text(buf.clone()));
+                                        tokens.push_back(Token::Text(buf.clone(), start));
                                         buf.clear();
                                     }
                                     context = Context::Statement;
+                                    start = line_at(content.len());
                                 },
                                 Some(c) => {
                                     // Change of mode -> statement.
                                     if !buf.is_empty() {
-                                        tokens.push_back(Token::Text(buf.clone()));
+                                        tokens.push_back(Token::Text(buf.clone(), start));
                                         buf.clear();
                                     }
                                     buf.push(c);
                                     context = Context::Statement;
+                                    start = line_at(content.len() + 1);
                                 },
                                 None => return Err(()),
                             } // match content.pop_front()
@@ -232,7 +258,7 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                         },
                         None => {
                             buf.push('<');
-                            tokens.push_back(Token::Text(buf.clone()));
+                            tokens.push_back(Token::Text(buf.clone(), start));
                             break;
                         },
                     } // match content.pop_front()
@@ -242,7 +268,7 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                     match buf.chars().last() {
                         Some('\n') => {
                             // '\n%'
-                            tokens.push_back(Token::Text(buf.clone()));
+                            tokens.push_back(Token::Text(buf.clone(), start));
                             buf.clear();
                         },
                         Some(_) => {
@@ -257,17 +283,19 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
                     match content.pop_front() {
                         Some('\n') => {
                             // '%\n'
-                            // Nothing to do.
+                            start = line_at(content.len());
                         },
                         Some('%') => {
                             // '%%'
                             // Escape '%'.
                             buf.push('%');
+                            start = line_at(content.len() + 1);
                         },
                         Some(c) => {
                             // '%.'
                             buf.push(c);
                             context = Context::StatementLine;
+                            start = line_at(content.len() + 1);
                         },
                         None => break,
                     } // match content.pop_front()
@@ -281,8 +309,16 @@ fn tokenize(content: &str) -> Result<VecDeque<Token>, ()> {
     Ok(tokens)
 }
 
+// Maps each line of a given piece of lua code to a template line.
+fn map_lines(lines: &mut Vec<usize>, line: usize, code: &str) {
+    for (idx, _) in code.split('\n').enumerate() {
+        lines.push(line + idx);
+    } // for
+}
+
 // Converts a given template into lua code.
-fn parse_template(content: &str) -> Result<String, ()> {
+// Also returns the template line of each lua line.
+fn parse_template(content: &str) -> Result<(String, Vec<usize>), ()> {
     let mut tokens =
             match tokenize(content) {
                 Ok(t) => t,
@@ -290,50 +326,124 @@ fn parse_template(content: &str) -> Result<String, ()> {
             };
 
     let mut res = String::new();
+    let mut lines = Vec::new();
     res.push_str("local _sb = {}\n");
-    loop {
-        let token =
-                match tokens.pop_front() {
-                    Some(t) => t,
-                    None => break,
-                };
-
+    lines.push(1);
+    while let Some(token) = tokens.pop_front() {
         match token {
-            Token::Expression(e) => {
+            Token::Expression(e, line) => {
                 #[cfg(feature = "debug")]
                 eprintln!("[Expression] '{}'", e);
 
                 res.push_str("table.insert(_sb, ");
                 res.push_str(&e);
                 res.push_str(")\n");
+                map_lines(&mut lines, line, &e);
             },
-            Token::Statement(s) => {
+            Token::Statement(s, line) => {
                 #[cfg(feature = "debug")]
                 eprintln!("[Statement] '{}'", s);
 
                 res.push_str(&s);
-                res.push_str("\n");
+                res.push('\n');
+                map_lines(&mut lines, line, &s);
             },
-            Token::Text(t) => {
+            Token::Text(t, line) => {
                 #[cfg(feature = "debug")]
                 eprintln!("[Text] '{}'", t);
 
-                res.push_str("table.insert(_sb, [[\n");
-                res.push_str(&t);
-                res.push_str("]])\n");
+                res.push_str("table.insert(_sb, ");
+                res.push_str(&value::to_lua_quoted(&t));
+                res.push_str(")\n");
+                lines.push(line);
             },
         }
-    } // loop
+    } // while
     res.push_str("ctx:set_output(_sb)\n");
+    lines.push(lines.last().copied().unwrap_or(1));
+
+    Ok((res, lines))
+}
+
+// The start of the first line of a compiled template, a lua comment followed by the digest of the lua code, the
+// template line of each lua line and the template path. A template starting with the same text is not taken for a
+// compiled template, unless the digest matches too.
+const COMPILED_HEADER: &str = "--rmarshal-template ";
 
-    Ok(res)
+// Returns the hexadecimal SHA-256 digest of given parts, separated by NUL bytes.
+fn hex_digest(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for (idx, part) in parts.iter().enumerate() {
+        if idx > 0 {
+            hasher.update([0]);
+        }
+        hasher.update(part);
+    } // for
+
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
 }
 
+// The environment variable naming the directory of compiled templates.
+const CACHE_DIR_VAR: &str = "RMARSHAL_CACHE_DIR";
+
 pub struct Template {
+    // The template path.
+    pub path: String,
+    // The lua code.
     pub content: String,
+    // The template line of each lua line.
+    lines: Vec<usize>,
 }
 
 impl Template {
+    // Compiles a given template content.
+    pub fn compile(path: &str, template_content: &str) -> Result<Self, ()> {
+        let (content, lines) = parse_template(template_content)?;
+
+        Ok(Template {
+            path: path.to_owned(),
+            content,
+            lines,
+        })
+    }
+
+    // Reads a template compiled with to_compiled(), whose lua code must match its digest.
+    // Any other content, including a template starting like a compiled one, is an error.
+    pub fn from_compiled(compiled: &str) -> Result<Self, ()> {
+        let (header, content) =
+                match compiled.split_once('\n') {
+                    Some((h, c)) => (h, c),
+                    None => return Err(()),
+                };
+        let header =
+                match header.strip_prefix(COMPILED_HEADER) {
+                    Some(h) => h,
+                    None => return Err(()),
+                };
+        let (digest, lines, path) =
+                match header.splitn(3, ' ').collect::<Vec<&str>>()[..] {
+                    [d, l, p] => (d, l, p),
+                    _ => return Err(()),
+                };
+        // A truncated or altered lua code is rejected.
+        if digest != hex_digest(&[content.as_bytes()]) {
+            return Err(());
+        }
+        let mut line_map = Vec::new();
+        for line in lines.split(',') {
+            match line.parse::<usize>() {
+                Ok(l) => line_map.push(l),
+                Err(_) => return Err(()),
+            }
+        } // for
+
+        Ok(Template {
+            path: path.to_owned(),
+            content: content.to_owned(),
+            lines: line_map,
+        })
+    }
+
     pub fn for_path(path: &str) -> Self {
         let template_content =
                 match std::fs::read_to_string(path) {
@@ -341,8 +451,147 @@ impl Template {
                     Err(e) => panic!("{}", e),
                 };
 
-        Template {
-            content: parse_template(&template_content).unwrap(),
+        if let Ok(t) = Template::from_compiled(&template_content) {
+            return t;
+        }
+
+        // Look for a compiled template in the cache.
+        let cache_path =
+                std::env::var_os(CACHE_DIR_VAR)
+                        .map(|dir| std::path::Path::new(&dir).join(Template::cache_name(path, &template_content)));
+        if let Some(ref cache_path) = cache_path {
+            if let Ok(compiled) = std::fs::read_to_string(cache_path) {
+                if let Ok(t) = Template::from_compiled(&compiled) {
+                    return t;
+                }
+            }
+        }
+
+        let template =
+                match Template::compile(path, &template_content) {
+                    Ok(t) => t,
+                    Err(_) => {
+                        eprintln!("{}: wrong template", path);
+                        std::process::exit(21);
+                    },
+                };
+        if let Some(ref cache_path) = cache_path {
+            // The cache is an optimization only, a failure to write is not an error.
+            // The file is renamed once written, so that the concurrent readers never see a partial file.
+            if let Some(dir) = cache_path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let tmp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+            if std::fs::write(&tmp_path, template.to_compiled()).is_err() || std::fs::rename(&tmp_path, cache_path).is_err() {
+                let _ = std::fs::remove_file(&tmp_path);
+            }
+        }
+
+        template
+    }
+
+    // Returns the file name of the compiled template in the cache.
+    // The name depends on the program version, the template path and the template content.
+    fn cache_name(path: &str, content: &str) -> String {
+        let mut name = hex_digest(&[crate::VERSION.as_bytes(), path.as_bytes(), content.as_bytes()]);
+        name.push_str(".lua");
+
+        name
+    }
+
+    // Returns the compiled template, i.e. the lua code with a header comment to locate errors in the template.
+    pub fn to_compiled(&self) -> String {
+        let lines: Vec<String> = self.lines.iter().map(|l| l.to_string()).collect();
+        let mut sb = String::new();
+        sb.push_str(COMPILED_HEADER);
+        sb.push_str(&hex_digest(&[self.content.as_bytes()]));
+        sb.push(' ');
+        sb.push_str(&lines.join(","));
+        sb.push(' ');
+        sb.push_str(&self.path);
+        sb.push('\n');
+        sb.push_str(&self.content);
+
+        sb
+    }
+
+    // Returns the chunk name to load the lua code with.
+    pub fn chunk_name(&self) -> String {
+        format!("={}", self.path)
+    }
+
+    // Returns the message of a given error, located in the template rather than in the lua code.
+    pub fn error_message(&self, err: &rlua::Error) -> String {
+        let msg = command::lua_error_message(err);
+        let prefix = format!("{}:", self.path);
+        if let Some(rem) = msg.strip_prefix(&prefix) {
+            if let Some((lua_line, rem)) = rem.split_once(':') {
+                if let Ok(lua_line) = lua_line.parse::<usize>() {
+                    if let Some(line) = self.lines.get(lua_line.wrapping_sub(1)) {
+                        return format!("{}{}:{}", prefix, line, rem);
+                    }
+                }
+            }
+        }
+
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_template {
+        use super::*;
+
+        #[test]
+        fn it_maps_lines() {
+            let (content, lines) = parse_template("a\n<% local x = 1 %>\nb <%= x %>\n% y()\nc\n").unwrap();
+            assert_eq!(content.lines().count(), lines.len());
+            assert_eq!(lines, vec![1, 1, 2, 2, 3, 3, 4, 5, 5]);
+        }
+
+        #[test]
+        fn it_escapes_text() {
+            let (content, _) = parse_template("a]]b\"c\\d\n").unwrap();
+            assert!(content.contains(r#"table.insert(_sb, "a]]b\"c\\d\n")"#));
+        }
+    }
+
+    mod template {
+        use super::*;
+
+        #[test]
+        fn it_reads_compiled() {
+            let template = Template::compile("tpl.txt", "a\n<%= x %>\n").unwrap();
+            let compiled = template.to_compiled();
+            assert!(Template::from_compiled(&compiled).is_ok());
+            let other = Template::from_compiled(&compiled).unwrap();
+            assert_eq!(other.path, "tpl.txt");
+            assert_eq!(other.content, template.content);
+            assert_eq!(other.lines, template.lines);
+        }
+
+        #[test]
+        fn it_rejects_truncated_compiled() {
+            let compiled = Template::compile("tpl.txt", "a\n<%= x %>\n").unwrap().to_compiled();
+            assert!(Template::from_compiled(&compiled[..compiled.len() - 5]).is_err());
+        }
+
+        #[test]
+        fn it_does_not_take_templates_for_compiled() {
+            assert!(Template::from_compiled("--rmarshal-template 1 tpl.txt\n<%= x %>\n").is_err());
+            let compiled = Template::compile("tpl.txt", "a\n<%= x %>\n").unwrap().to_compiled();
+            assert!(Template::from_compiled(&compiled.replace("table.insert", "table.insert ")).is_err());
+        }
+
+        #[test]
+        fn it_compiles_into_lua() {
+            let compiled = Template::compile("tpl.txt", "a\n<%= x %>\n").unwrap().to_compiled();
+            let lua = rlua::Lua::new();
+            let res = lua.context(|lua_ctx| lua_ctx.load(&compiled).into_function().map(|_| ()));
+            assert!(res.is_ok());
         }
     }
 }
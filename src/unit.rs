@@ -1,30 +1,61 @@
+use crate::canonical::DigestAlgorithm;
+use crate::check::ReportFormat;
+use crate::diff::DiffOptions;
+use crate::edit::Edit;
+use crate::flatten::FlattenOptions;
+use crate::frontmatter::MetaFormat;
+use crate::ini::IniOptions;
+use crate::query::Query;
+use crate::value::MergeStrategy;
+
+const ENV_PATH_SUFFIX: &str = ".env";
+const HCL_PATH_SUFFIXES: [&str; 2] = [".hcl", ".tfvars"];
+const INI_PATH_SUFFIX: &str = ".ini";
 const JSON_PATH_SUFFIX: &str = ".json";
 const LUA_PATH_SUFFIX: &str = ".lua";
+const PROPERTIES_PATH_SUFFIX: &str = ".properties";
+const RON_PATH_SUFFIX: &str = ".ron";
 const TOML_PATH_SUFFIX: &str = ".toml";
 const YAML_PATH_SUFFIX: &str = ".yaml";
 
-#[derive(Debug, PartialEq, Eq)]
+const JINJA_PATH_SUFFIXES: [&str; 3] = [".j2", ".jinja", ".jinja2"];
+
+#[derive(Debug, Default, PartialEq, Eq)]
 pub enum FileFormat {
+    #[default]
     Plain,
+    Env,
+    FrontMatter,
+    Hcl,
+    HtmlTable,
+    Ini,
     Json,
     Lua,
+    MarkdownTable,
+    Properties,
+    Ron,
+    Table,
     Toml,
     Yaml,
 }
 
-impl Default for FileFormat {
-    fn default() -> Self {
-        FileFormat::Plain
-    }
-}
-
 impl FileFormat {
     pub fn for_path(path: &str) -> Self {
         let lc_path = path.to_lowercase();
-        if lc_path.ends_with(JSON_PATH_SUFFIX) {
+        if lc_path.ends_with(ENV_PATH_SUFFIX) {
+            FileFormat::Env
+        } else if HCL_PATH_SUFFIXES.iter().any(|s| lc_path.ends_with(s)) {
+            FileFormat::Hcl
+        } else if lc_path.ends_with(INI_PATH_SUFFIX) {
+            FileFormat::Ini
+        } else if lc_path.ends_with(JSON_PATH_SUFFIX) {
             FileFormat::Json
         } else if lc_path.ends_with(LUA_PATH_SUFFIX) {
             FileFormat::Lua
+        } else if lc_path.ends_with(PROPERTIES_PATH_SUFFIX) {
+            FileFormat::Properties
+        } else if lc_path.ends_with(RON_PATH_SUFFIX) {
+            FileFormat::Ron
         } else if lc_path.ends_with(TOML_PATH_SUFFIX) {
             FileFormat::Toml
         } else if lc_path.ends_with(YAML_PATH_SUFFIX) {
@@ -34,12 +65,26 @@ impl FileFormat {
         }
     }
 
+    // Tests whether the documents of this FileFormat are flat objects of strings.
+    pub fn is_flat(&self) -> bool {
+        matches!(self, FileFormat::Env | FileFormat::Properties)
+    }
+
     // Returns a FileFormat for a given string representation.
     pub fn for_str(format: &str) -> Result<Self, ()> {
         match format {
             "plain" => Ok(FileFormat::Plain),
+            "env" => Ok(FileFormat::Env),
+            "frontmatter" => Ok(FileFormat::FrontMatter),
+            "hcl" => Ok(FileFormat::Hcl),
+            "html-table" => Ok(FileFormat::HtmlTable),
+            "ini" => Ok(FileFormat::Ini),
             "json" => Ok(FileFormat::Json),
             "lua" => Ok(FileFormat::Lua),
+            "markdown-table" => Ok(FileFormat::MarkdownTable),
+            "properties" => Ok(FileFormat::Properties),
+            "ron" => Ok(FileFormat::Ron),
+            "table" => Ok(FileFormat::Table),
             "toml" => Ok(FileFormat::Toml),
             "yaml" => Ok(FileFormat::Yaml),
             _ => Err(()),
@@ -47,7 +92,34 @@ impl FileFormat {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateEngine {
+    #[default]
+    Lua,
+    Jinja,
+}
+
+impl TemplateEngine {
+    pub fn for_path(path: &str) -> Self {
+        let lc_path = path.to_lowercase();
+        if JINJA_PATH_SUFFIXES.iter().any(|s| lc_path.ends_with(s)) {
+            TemplateEngine::Jinja
+        } else {
+            TemplateEngine::Lua
+        }
+    }
+
+    // Returns a TemplateEngine for a given string representation.
+    pub fn for_str(engine: &str) -> Result<Self, ()> {
+        match engine {
+            "lua" => Ok(TemplateEngine::Lua),
+            "jinja" => Ok(TemplateEngine::Jinja),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum DocumentHint {
     Any,
     Nil,
@@ -113,8 +185,22 @@ pub struct UnitFile {
     pub eol: Option<bool>,
     // To reorder object elements of TOML.
     pub fix: Option<bool>,
-    // The JSON pretty format.
+    // The expansion of include directives, disabled by default. Input only.
+    pub includes: Option<bool>,
+    // The JSON and RON pretty format.
     pub pretty: Option<bool>,
+    // The JSON canonical format, as of RFC 8785.
+    pub canonical: Option<bool>,
+    // The selected and ordered columns of the tables.
+    pub columns: Option<Vec<String>>,
+    // The format of the written front matter.
+    pub meta: Option<MetaFormat>,
+    // The HCL objects written as blocks.
+    pub blocks: Option<bool>,
+    // How INI is read and written.
+    pub ini: Option<IniOptions>,
+    // The separator of the flat keys of env and properties, to nest or flatten the values.
+    pub separator: Option<String>,
     // For multiple documents within the same file.
     pub stream: Option<isize>,
 }
@@ -127,7 +213,14 @@ impl UnitFile {
             dots: None,
             eol: None,
             fix: None,
+            includes: None,
             pretty: None,
+            canonical: None,
+            columns: None,
+            meta: None,
+            blocks: None,
+            ini: None,
+            separator: None,
             stream: None,
         }
     }
@@ -147,74 +240,115 @@ impl UnitFile {
             dots: None,
             eol: None,
             fix: None,
+            includes: None,
             pretty: None,
+            canonical: None,
+            columns: None,
+            meta: None,
+            blocks: None,
+            ini: None,
+            separator: None,
             stream: None,
         }
     }
 
+    pub fn has_blocks(&self) -> bool {
+        matches!(self.blocks, Some(true))
+    }
+
     pub fn has_dots(&self) -> bool {
-        if let Some(true) = self.dots {
-            true
-        } else {
-            false
-        }
+        matches!(self.dots, Some(true))
     }
 
     pub fn has_eol(&self) -> bool {
-        if let Some(true) = self.eol {
-            true
-        } else {
-            false
-        }
+        matches!(self.eol, Some(true))
     }
 
     pub fn has_fix(&self) -> bool {
-        if let Some(true) = self.fix {
-            true
-        } else {
-            false
-        }
+        matches!(self.fix, Some(true))
+    }
+
+    pub fn has_includes(&self) -> bool {
+        matches!(self.includes, Some(true))
     }
 
     pub fn has_pretty(&self) -> bool {
-        if let Some(true) = self.pretty {
-            true
-        } else {
-            false
-        }
+        matches!(self.pretty, Some(true))
+    }
+
+    pub fn has_canonical(&self) -> bool {
+        matches!(self.canonical, Some(true))
     }
 
     pub fn has_stream(&self) -> bool {
-        if let Some(_) = self.stream {
-            true
-        } else {
-            false
-        }
+        self.stream.is_some()
     }
 }
 
 #[derive(Debug, Default)]
 pub struct UnitCommand {
-    // For Lua and Template commands.
+    // For Lua, Template and validate commands, and the vars of the interpolate command.
     pub path: Option<String>,
     // For merge command.
     pub depth: Option<isize>,
+    // For merge command: how arrays and given paths are merged.
+    pub strategy: Option<MergeStrategy>,
+    // For merge command: apply the documents as merge patches.
+    pub patch: Option<bool>,
+    // For diff command.
+    pub diff: Option<DiffOptions>,
+    // For query command.
+    pub query: Option<Query>,
+    // For set, delete and rename commands.
+    pub edit: Option<Edit>,
+    // For validate command: fill the documents with the default values of the schema.
+    pub defaults: Option<bool>,
+    // For Lua, Template and interpolate commands: fail on undefined variables.
+    // For check command: also report what reading the inputs would silently lose or accept.
+    pub strict: Option<bool>,
+    // For check command: the format of the report.
+    pub report: Option<ReportFormat>,
+    // For digest command.
+    pub digest: Option<DigestAlgorithm>,
+    // For interpolate command: type the strings made of a single placeholder.
+    pub retype: Option<bool>,
+    // For flatten and unflatten commands.
+    pub flatten: Option<FlattenOptions>,
+    // For Template and query commands: render once per document, or push each match as a document.
+    pub each: Option<bool>,
+    // For Template command: the format to read the rendered text with.
+    pub format: Option<FileFormat>,
+    // For Template command: the template engine, inferred from the path by default.
+    pub engine: Option<TemplateEngine>,
 }
 
 impl UnitCommand {
-    pub fn for_path(path: &str) -> Self {
-        UnitCommand {
-            path: Some(path.to_owned()),
-            depth: None,
-        }
-    }
-
     // pub fn for_depth(depth: isize) -> Self {
     //     UnitCommand {
     //         path: None,
     //         depth: Some(depth),
     //     }
     // }
+
+    pub fn has_patch(&self) -> bool {
+        matches!(self.patch, Some(true))
+    }
+
+    pub fn has_strict(&self) -> bool {
+        matches!(self.strict, Some(true))
+    }
+
+    pub fn has_defaults(&self) -> bool {
+        matches!(self.defaults, Some(true))
+    }
+
+    pub fn has_each(&self) -> bool {
+        matches!(self.each, Some(true))
+    }
+
+    pub fn has_retype(&self) -> bool {
+        matches!(self.retype, Some(true))
+    }
 }
 
 // Parameter Unit.
@@ -225,14 +359,28 @@ pub enum Unit {
     // Input or output:
     File(UnitFile),
     // Commands:
-    Check,
+    Canonicalize,
+    Check(UnitCommand),
+    CompileTemplate(UnitCommand),
     Concat,
     Copy,
+    Diff(UnitCommand),
+    Digest(UnitCommand),
+    Edit(UnitCommand),
+    Flatten(UnitCommand),
+    InferSchema,
+    Interpolate(UnitCommand),
     Merge(UnitCommand),
+    MergePatchDiff,
     Pack,
+    JsonPatch,
+    Query(UnitCommand),
     Unpack,
+    Validate(UnitCommand),
     Render(UnitCommand),
+    ResolveRefs,
     Transform(UnitCommand),
+    Unflatten(UnitCommand),
 }
 
 #[cfg(test)]
@@ -269,6 +417,33 @@ mod tests {
                 assert_eq!(yaml, FileFormat::Yaml);
             }
 
+            #[test]
+            fn it_create_env() {
+                assert_eq!(FileFormat::for_path(".env"), FileFormat::Env);
+                assert_eq!(FileFormat::for_path("prod.env"), FileFormat::Env);
+            }
+
+            #[test]
+            fn it_create_hcl() {
+                assert_eq!(FileFormat::for_path("main.hcl"), FileFormat::Hcl);
+                assert_eq!(FileFormat::for_path("prod.tfvars"), FileFormat::Hcl);
+            }
+
+            #[test]
+            fn it_create_ini() {
+                assert_eq!(FileFormat::for_path("tox.ini"), FileFormat::Ini);
+            }
+
+            #[test]
+            fn it_create_properties() {
+                assert_eq!(FileFormat::for_path("app.properties"), FileFormat::Properties);
+            }
+
+            #[test]
+            fn it_create_ron() {
+                assert_eq!(FileFormat::for_path("config.ron"), FileFormat::Ron);
+            }
+
             #[test]
             fn it_create_txt() {
                 let txt = FileFormat::for_path("yo.txt");
@@ -316,4 +491,50 @@ mod tests {
             }
         }
     }
+
+    mod template_engine {
+        use super::*;
+
+        mod for_path {
+            use super::*;
+
+            #[test]
+            fn it_create_jinja() {
+                assert_eq!(TemplateEngine::for_path("yo.j2"), TemplateEngine::Jinja);
+                assert_eq!(TemplateEngine::for_path("yo.jinja"), TemplateEngine::Jinja);
+                assert_eq!(TemplateEngine::for_path("yo.jinja2"), TemplateEngine::Jinja);
+            }
+
+            #[test]
+            fn it_create_lua() {
+                assert_eq!(TemplateEngine::for_path("yo.txt"), TemplateEngine::Lua);
+            }
+        }
+
+        mod for_str {
+            use super::*;
+
+            #[test]
+            fn it_create_jinja() {
+                assert_eq!(TemplateEngine::for_str("jinja"), Ok(TemplateEngine::Jinja));
+            }
+
+            #[test]
+            fn it_does_not_create_foo() {
+                assert_eq!(TemplateEngine::for_str("foo"), Err(()));
+            }
+        }
+    }
+
+    mod unit_file {
+        use super::*;
+
+        #[test]
+        fn it_disables_includes_by_default() {
+            let mut file = UnitFile::for_path("doc.yaml");
+            assert!(!file.has_includes());
+            file.includes = Some(true);
+            assert!(file.has_includes());
+        }
+    }
 }
@@ -69,15 +69,10 @@ impl FlaggedOption {
             } else {
                 // With qualifiers.
                 let mut qualifiers = IndexMap::new();
-                loop {
-                    match comps.pop_front() {
-                        Some(c) => {
-                            let (key, val) = parse_pair(c)?;
-                            qualifiers.insert(key, val);
-                        },
-                        None => break,
-                    }
-                } // loop
+                while let Some(c) = comps.pop_front() {
+                    let (key, val) = parse_pair(c)?;
+                    qualifiers.insert(key, val);
+                } // while
 
                 Ok(FlaggedOption {
                     short: false,
@@ -157,7 +152,7 @@ mod tests {
         #[test]
         fn it_parse_long() {
             let res = FlaggedOption::from_str("--foo").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             assert_eq!(res.qualifiers, None);
@@ -166,7 +161,7 @@ mod tests {
         #[test]
         fn it_parse_long_value() {
             let res = FlaggedOption::from_str("--foo=bar").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             assert_eq!(res.qualifiers, None);
@@ -175,7 +170,7 @@ mod tests {
         #[test]
         fn it_parse_long_qualifier() {
             let res = FlaggedOption::from_str("--foo:alfa").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             let qualifiers = res.qualifiers.unwrap();
@@ -187,7 +182,7 @@ mod tests {
         #[test]
         fn it_parse_long_qualifier_value() {
             let res = FlaggedOption::from_str("--foo:alfa=1").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             let qualifiers = res.qualifiers.unwrap();
@@ -199,7 +194,7 @@ mod tests {
         #[test]
         fn it_parse_long_qualifiers() {
             let res = FlaggedOption::from_str("--foo:alfa:bravo").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             let qualifiers = res.qualifiers.unwrap();
@@ -213,7 +208,7 @@ mod tests {
         #[test]
         fn it_parse_long_qualifiers_value() {
             let res = FlaggedOption::from_str("--foo:alfa=1:bravo").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             let qualifiers = res.qualifiers.unwrap();
@@ -227,7 +222,7 @@ mod tests {
         #[test]
         fn it_parse_long_qualifiers_value_value() {
             let res = FlaggedOption::from_str("--foo:alfa=1:bravo=2").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, None);
             let qualifiers = res.qualifiers.unwrap();
@@ -241,7 +236,7 @@ mod tests {
         #[test]
         fn it_parse_long_value_qualifier() {
             let res = FlaggedOption::from_str("--foo=bar:alfa").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             let qualifiers = res.qualifiers.unwrap();
@@ -253,7 +248,7 @@ mod tests {
         #[test]
         fn it_parse_long_value_qualifier_value() {
             let res = FlaggedOption::from_str("--foo=bar:alfa=1").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             let qualifiers = res.qualifiers.unwrap();
@@ -265,7 +260,7 @@ mod tests {
         #[test]
         fn it_parse_long_value_qualifiers() {
             let res = FlaggedOption::from_str("--foo=bar:alfa:bravo").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             let qualifiers = res.qualifiers.unwrap();
@@ -279,7 +274,7 @@ mod tests {
         #[test]
         fn it_parse_long_value_qualifiers_value() {
             let res = FlaggedOption::from_str("--foo=bar:alfa=1:bravo").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             let qualifiers = res.qualifiers.unwrap();
@@ -293,7 +288,7 @@ mod tests {
         #[test]
         fn it_parse_long_value_qualifiers_value_value() {
             let res = FlaggedOption::from_str("--foo=bar:alfa=1:bravo=2").unwrap();
-            assert_eq!(res.short, false);
+            assert!(!res.short);
             assert_eq!(res.option, "foo".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             let qualifiers = res.qualifiers.unwrap();
@@ -307,7 +302,7 @@ mod tests {
         #[test]
         fn it_parse_short() {
             let res = FlaggedOption::from_str("-f").unwrap();
-            assert_eq!(res.short, true);
+            assert!(res.short);
             assert_eq!(res.option, "f".to_owned());
             assert_eq!(res.value, None);
             assert_eq!(res.qualifiers, None);
@@ -316,7 +311,7 @@ mod tests {
         #[test]
         fn it_parse_short_value() {
             let res = FlaggedOption::from_str("-fbar").unwrap();
-            assert_eq!(res.short, true);
+            assert!(res.short);
             assert_eq!(res.option, "f".to_owned());
             assert_eq!(res.value, Some("bar".to_owned()));
             assert_eq!(res.qualifiers, None);
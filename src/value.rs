@@ -10,7 +10,7 @@ use serde_json::{Value as JsonValue};
 use serde_yaml::{Value as YamlValue};
 use toml::{Value as TomlValue};
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Nil,
     Boolean(bool),
@@ -44,9 +44,88 @@ impl Serialize for Value {
     }
 }
 
+// The ways of merging 2 Values.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum MergeMode {
+    // Merges Array elements index by index, and Object entries key by key.
+    #[default]
+    Merge,
+    // Keeps the right Value.
+    Replace,
+    // Concatenates Arrays.
+    Append,
+    // Concatenates Arrays, skipping the right elements already present.
+    Union,
+    // Merges the Object elements of Arrays having the same value for a given key.
+    ByKey(String),
+}
+
+impl MergeMode {
+    pub fn for_str(mode: &str) -> Result<Self, ()> {
+        match mode {
+            "merge" => Ok(MergeMode::Merge),
+            "replace" => Ok(MergeMode::Replace),
+            "append" => Ok(MergeMode::Append),
+            "union" => Ok(MergeMode::Union),
+            _ => match mode.strip_prefix("by-key:") {
+                Some(key) if !key.is_empty() => Ok(MergeMode::ByKey(key.to_owned())),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+// The strategy of a merge.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeStrategy {
+    // The mode of Array Values.
+    pub arrays: MergeMode,
+    // The modes of given paths, as the tokens of JSON pointers. A '*' token matches any key or index.
+    pub paths: Vec<(Vec<String>, MergeMode)>,
+}
+
+impl MergeStrategy {
+    // Returns the mode of a given path. The last matching JSON pointer wins.
+    fn mode_at(&self, path: &[String], is_array: bool) -> &MergeMode {
+        for (tokens, mode) in self.paths.iter().rev() {
+            if tokens_match(tokens, path) {
+                return mode;
+            }
+        } // for
+
+        if is_array {
+            &self.arrays
+        } else {
+            &MergeMode::Merge
+        }
+    }
+}
+
+// Splits a given JSON pointer into its unescaped tokens.
+pub fn pointer_tokens(pointer: &str) -> Result<Vec<String>, ()> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(());
+    }
+
+    Ok(pointer[1..].split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+// Tests whether the tokens of a JSON pointer match a given path.
+fn tokens_match(tokens: &[String], path: &[String]) -> bool {
+    tokens.len() == path.len() && tokens.iter().zip(path).all(|(t, p)| t == "*" || t == p)
+}
+
 // Creates a new Value by merging 2 given Values.
 // The depth is meant for Array and Object Values. A negative value indicates an infinite depth.
-pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
+pub fn merge_values(left: &Value, right: &Value, depth: isize, strategy: &MergeStrategy) -> Value {
+    merge_values_at(left, right, depth, strategy, &mut Vec::new())
+}
+
+// Merges 2 given Values located at a given path.
+fn merge_values_at(left: &Value, right: &Value, depth: isize, strategy: &MergeStrategy, path: &mut Vec<String>) -> Value {
     if depth == 0 {
         return right.clone();
     }
@@ -60,6 +139,50 @@ pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
                 // Both Values are Array.
                 let mut s = Vec::new();
 
+                match strategy.mode_at(path, true) {
+                    MergeMode::Replace => return right.clone(),
+                    MergeMode::Append => {
+                        s.extend(l.iter().cloned());
+                        s.extend(r.iter().cloned());
+                        return Value::Array(s);
+                    },
+                    MergeMode::Union => {
+                        s.extend(l.iter().cloned());
+                        for right_val in r.iter() {
+                            if !s.contains(right_val) {
+                                s.push(right_val.clone());
+                            }
+                        } // for
+                        return Value::Array(s);
+                    },
+                    MergeMode::ByKey(key) => {
+                        s.extend(l.iter().cloned());
+                        for right_val in r.iter() {
+                            let found =
+                                    match right_val {
+                                        Value::Object(o) => match o.get(key) {
+                                            Some(id) => s.iter().position(|v| match v {
+                                                Value::Object(lo) => lo.get(key) == Some(id),
+                                                _ => false,
+                                            }),
+                                            None => None,
+                                        },
+                                        _ => None,
+                                    };
+                            match found {
+                                Some(idx) => {
+                                    path.push(idx.to_string());
+                                    s[idx] = merge_values_at(&s[idx], right_val, depth, strategy, path);
+                                    path.pop();
+                                },
+                                None => s.push(right_val.clone()),
+                            }
+                        } // for
+                        return Value::Array(s);
+                    },
+                    MergeMode::Merge => {},
+                }
+
                 let left_len = l.len();
                 let right_len = r.len();
                 let len =
@@ -83,7 +206,9 @@ pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
                                 (false, &dummy)
                             };
                     if has_left && has_right {
-                        s.push(merge_values(left_val, right_val, depth))
+                        path.push(idx.to_string());
+                        s.push(merge_values_at(left_val, right_val, depth, strategy, path));
+                        path.pop();
                     } else if has_left {
                         s.push(left_val.clone());
                     } else if has_right {
@@ -100,6 +225,10 @@ pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
         Value::Object(l) => match right {
             Value::Object(r) => {
                 // Both Values are Object.
+                if *strategy.mode_at(path, false) == MergeMode::Replace {
+                    return right.clone();
+                }
+
                 let mut s = IndexMap::new();
 
                 let mut keys = IndexSet::new();
@@ -117,7 +246,9 @@ pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
                                 None => (false, &dummy),
                             };
                     if has_left && has_right {
-                        s.insert((*key).clone(), merge_values(left_val, right_val, depth));
+                        path.push((*key).clone());
+                        s.insert((*key).clone(), merge_values_at(left_val, right_val, depth, strategy, path));
+                        path.pop();
                     } else if has_left {
                         s.insert((*key).clone(), left_val.clone());
                     } else if has_right {
@@ -133,10 +264,105 @@ pub fn merge_values(left: &Value, right: &Value, depth: isize) -> Value {
     }
 }
 
+// Creates a new Value by applying a given merge patch to a given target, as of RFC 7396.
+// A Nil entry of the patch removes the entry of the target, and any non-Object Value replaces the target.
+pub fn merge_patch(target: &Value, patch: &Value) -> Value {
+    match patch {
+        Value::Object(p) => {
+            let mut s =
+                    match target {
+                        Value::Object(t) => t.clone(),
+                        _ => IndexMap::new(),
+                    };
+            for (key, patch_val) in p.iter() {
+                match patch_val {
+                    Value::Nil => {
+                        s.shift_remove(key);
+                    },
+                    _ => {
+                        let target_val = s.get(key).unwrap_or(&Value::Nil);
+                        let res = merge_patch(target_val, patch_val);
+                        s.insert(key.clone(), res);
+                    },
+                }
+            } // for
+
+            Value::Object(s)
+        },
+        _ => patch.clone(),
+    }
+}
+
+// Fails when a given Value of a merge patch, located at a given path, holds a Nil Object entry.
+fn check_patch_nil(value: &Value, path: &mut Vec<String>) -> Result<(), String> {
+    if let Value::Object(o) = value {
+        for (k, v) in o.iter() {
+            path.push(k.clone());
+            if *v == Value::Nil {
+                return Err(format!("right document holds null at {}; not expressible as a merge patch", crate::patch::to_pointer(path)));
+            }
+            check_patch_nil(v, path)?;
+            path.pop();
+        } // for
+    }
+
+    Ok(())
+}
+
+fn merge_patch_diff_at<F>(left: &Value, right: &Value, eq: &F, path: &mut Vec<String>) -> Result<Value, String>
+        where F: Fn(&Value, &Value) -> bool {
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            let mut s = IndexMap::new();
+
+            for key in l.keys() {
+                if !r.contains_key(key) {
+                    s.insert(key.clone(), Value::Nil);
+                }
+            } // for
+            for (key, right_val) in r.iter() {
+                path.push(key.clone());
+                match l.get(key) {
+                    Some(left_val) if eq(left_val, right_val) => {},
+                    // Nil removes the entry instead of setting it.
+                    Some(_) | None if *right_val == Value::Nil => {
+                        return Err(format!("right document holds null at {}; not expressible as a merge patch", crate::patch::to_pointer(path)));
+                    },
+                    Some(left_val) => {
+                        s.insert(key.clone(), merge_patch_diff_at(left_val, right_val, eq, path)?);
+                    },
+                    None => {
+                        check_patch_nil(right_val, path)?;
+                        s.insert(key.clone(), right_val.clone());
+                    },
+                }
+                path.pop();
+            } // for
+
+            Ok(Value::Object(s))
+        },
+        _ => {
+            check_patch_nil(right, path)?;
+            Ok(right.clone())
+        },
+    }
+}
+
+// Creates the minimal merge patch turning a given left Value into a given right Value, as of RFC 7396.
+// Since Nil removes entries, a Nil entry of the right Value can't be expressed and is an error.
+pub fn merge_patch_diff(left: &Value, right: &Value) -> Result<Value, String> {
+    merge_patch_diff_by(left, right, |l, r| l == r)
+}
+
+// Creates the minimal merge patch like merge_patch_diff, where entries equal by a given function are left out.
+pub fn merge_patch_diff_by<F>(left: &Value, right: &Value, eq: F) -> Result<Value, String>
+        where F: Fn(&Value, &Value) -> bool {
+    merge_patch_diff_at(left, right, &eq, &mut Vec::new())
+}
+
 // Tests whether a given Lua table can be treated as an array.
 fn is_lua_table_array(table: LuaTable) -> bool {
-    let mut idx = 1;
-    for pair in table.pairs::<LuaValue, LuaValue>() {
+    for (idx, pair) in (1..).zip(table.pairs::<LuaValue, LuaValue>()) {
         let (k, _) = pair.unwrap();
         match k {
             LuaValue::Integer(i) => {
@@ -146,7 +372,6 @@ fn is_lua_table_array(table: LuaTable) -> bool {
             },
             _ => return false,
         }
-        idx += 1;
     } // for
 
     true
@@ -337,6 +562,26 @@ pub fn from_processed_template(table: LuaTable) -> String {
     res
 }
 
+// Converts a given string into a quoted lua string literal.
+pub fn to_lua_quoted(s: &str) -> String {
+    let mut sb = String::new();
+    sb.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => sb.push_str("\\\""),
+            '\\' => sb.push_str("\\\\"),
+            '\n' => sb.push_str("\\n"),
+            '\r' => sb.push_str("\\r"),
+            '\t' => sb.push_str("\\t"),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => sb.push_str(&format!("\\{:03}", c as u32)),
+            c => sb.push(c),
+        }
+    } // for
+    sb.push('"');
+
+    sb
+}
+
 // Converts a given internal value into lua.
 pub fn to_lua_string(value: &Value) -> String {
     match value {
@@ -354,18 +599,11 @@ pub fn to_lua_string(value: &Value) -> String {
         Value::Float(f) => {
             format!("{}", f)
         },
-        Value::String(s) => {
-            let mut sb = String::new();
-            sb.push('"');
-            sb.push_str(s);
-            sb.push('"');
-
-            sb
-        },
+        Value::String(s) => to_lua_quoted(s),
         Value::Array(a) => {
             let mut sb = String::new();
             sb.push_str("Array:new({");
-            for (_, elem) in a.iter().enumerate() {
+            for elem in a.iter() {
                 sb.push_str(&to_lua_string(elem));
                 sb.push(',');
             } // for
@@ -378,9 +616,7 @@ pub fn to_lua_string(value: &Value) -> String {
             sb.push_str("Object:new({");
             for (k, v) in o {
                 sb.push('{');
-                sb.push('"');
-                sb.push_str(k);
-                sb.push('"');
+                sb.push_str(&to_lua_quoted(k));
                 sb.push(',');
                 sb.push_str(&to_lua_string(v));
                 sb.push('}');
@@ -393,69 +629,101 @@ pub fn to_lua_string(value: &Value) -> String {
     }
 }
 
-// Converts a given JSON value into an internal Value.
-fn from_json_value(value: &JsonValue) -> Value {
+// Returns the string representation of a given scalar, where nil is an empty string.
+pub fn scalar_to_string(value: &Value) -> Option<String> {
     match value {
-        JsonValue::Null => Value::Nil,
-        JsonValue::Bool(v) => Value::Boolean(*v),
+        Value::Nil => Some(String::new()),
+        Value::Boolean(b) => Some(b.to_string()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+// Returns the error of an integer overflowing i64, located at a given path.
+fn overflow_error(number: &impl std::fmt::Display, path: &[String]) -> String {
+    format!("integer {} overflows at '{}'", number, crate::patch::to_pointer(path))
+}
+
+// Converts a given JSON value, located at a given path, into an internal Value.
+fn from_json_value(value: &JsonValue, path: &mut Vec<String>) -> Result<Value, String> {
+    match value {
+        JsonValue::Null => Ok(Value::Nil),
+        JsonValue::Bool(v) => Ok(Value::Boolean(*v)),
         JsonValue::Number(v) => {
-            if v.is_f64() {
-                Value::Float(v.as_f64().unwrap())
-            } else {
-                Value::Integer(v.as_i64().unwrap())
+            // An integer overflowing i64 would lose its precision as a Float.
+            match v.as_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None if v.is_u64() => Err(overflow_error(v, path)),
+                None => Ok(Value::Float(v.as_f64().unwrap())),
             }
         },
-        JsonValue::String(v) => Value::String(v.clone()),
+        JsonValue::String(v) => Ok(Value::String(v.clone())),
         JsonValue::Array(a) => {
             let mut new_array = Vec::new();
-            for v in a {
-                new_array.push(from_json_value(v));
+            for (idx, v) in a.iter().enumerate() {
+                path.push(idx.to_string());
+                new_array.push(from_json_value(v, path)?);
+                path.pop();
             } // for
 
-            Value::Array(new_array)
+            Ok(Value::Array(new_array))
         },
         JsonValue::Object(o) => {
             let mut new_obj = IndexMap::new();
             for (k, v) in o {
-                new_obj.insert(k.clone(), from_json_value(v));
+                path.push(k.clone());
+                new_obj.insert(k.clone(), from_json_value(v, path)?);
+                path.pop();
             } // for
 
-            Value::Object(new_obj)
+            Ok(Value::Object(new_obj))
         },
     }
 }
 
 // Converts a given JSON string representation into an internal Value.
-pub fn from_json_str(content: &str) -> Result<Value, ()> {
+pub fn from_json_str(content: &str) -> Result<Value, String> {
     let json_val =
             match serde_json::from_str(content) {
                 Ok(v) => v,
-                Err(_) => return Err(()),
+                Err(e) => return Err(e.to_string()),
             };
 
-    Ok(from_json_value(&json_val))
+    from_json_value(&json_val, &mut Vec::new())
+}
+
+// Creates a new Value from a given JSON document, which must be valid.
+#[cfg(test)]
+pub(crate) fn new_value(json: &str) -> Value {
+    from_json_str(json).unwrap()
 }
 
-// Converts a given YAML value into an internal Value.
-fn from_yaml_value(value: &YamlValue) -> Value {
+// Converts a given YAML value, located at a given path, into an internal Value.
+// The scalar entry names are converted into strings.
+fn from_yaml_value(value: &YamlValue, path: &mut Vec<String>) -> Result<Value, String> {
     match value {
-        YamlValue::Null => Value::Nil,
-        YamlValue::Bool(v) => Value::Boolean(*v),
+        YamlValue::Null => Ok(Value::Nil),
+        YamlValue::Bool(v) => Ok(Value::Boolean(*v)),
         YamlValue::Number(v) => {
-            if v.is_f64() {
-                Value::Float(v.as_f64().unwrap())
-            } else {
-                Value::Integer(v.as_i64().unwrap())
+            // An integer overflowing i64 would lose its precision as a Float.
+            match v.as_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None if v.is_u64() => Err(overflow_error(v, path)),
+                None => Ok(Value::Float(v.as_f64().unwrap())),
             }
         },
-        YamlValue::String(v) => Value::String(v.clone()),
+        YamlValue::String(v) => Ok(Value::String(v.clone())),
         YamlValue::Sequence(a) => {
             let mut new_array = Vec::new();
-            for v in a {
-                new_array.push(from_yaml_value(v));
+            for (idx, v) in a.iter().enumerate() {
+                path.push(idx.to_string());
+                new_array.push(from_yaml_value(v, path)?);
+                path.pop();
             } // for
 
-            Value::Array(new_array)
+            Ok(Value::Array(new_array))
         },
         YamlValue::Mapping(o) => {
             let mut new_obj = IndexMap::new();
@@ -463,26 +731,32 @@ fn from_yaml_value(value: &YamlValue) -> Value {
                 let name =
                         match k {
                             YamlValue::String(n) => n.clone(),
-                            _ => panic!("wrong object entry name"),
+                            YamlValue::Null => "null".to_owned(),
+                            YamlValue::Bool(b) => b.to_string(),
+                            YamlValue::Number(n) => n.to_string(),
+                            _ => return Err("wrong object entry name".to_owned()),
                         };
 
-                new_obj.insert(name, from_yaml_value(v));
+                path.push(name.clone());
+                let new_val = from_yaml_value(v, path)?;
+                path.pop();
+                new_obj.insert(name, new_val);
             } // for
 
-            Value::Object(new_obj)
+            Ok(Value::Object(new_obj))
         },
     }
 }
 
 // Converts a given YAML string representation into an internal Value.
-pub fn from_yaml_str(content: &str) -> Result<Value, ()> {
+pub fn from_yaml_str(content: &str) -> Result<Value, String> {
     let yaml_val =
             match serde_yaml::from_str(content) {
                 Ok(v) => v,
-                Err(_) => return Err(()),
+                Err(e) => return Err(e.to_string()),
             };
 
-    Ok(from_yaml_value(&yaml_val))
+    from_yaml_value(&yaml_val, &mut Vec::new())
 }
 
 // Converts a given TOML value into an internal Value.
@@ -513,11 +787,11 @@ fn from_toml_value(value: &TomlValue) -> Value {
 }
 
 // Converts a given TOML string representation into an internal Value.
-pub fn from_toml_str(content: &str) -> Result<Value, ()> {
+pub fn from_toml_str(content: &str) -> Result<Value, String> {
     let toml_val =
             match toml::from_str(content) {
                 Ok(v) => v,
-                Err(_) => return Err(()),
+                Err(e) => return Err(e.to_string()),
             };
 
     Ok(from_toml_value(&toml_val))
@@ -555,3 +829,153 @@ pub fn fix_toml(value: &Value) -> Value {
         value.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_object(entries: &[(&str, Value)]) -> Value {
+        Value::Object(entries.iter().map(|(k, v)| ((*k).to_owned(), v.clone())).collect())
+    }
+
+    fn new_container(name: &str, image: &str) -> Value {
+        new_object(&[("name", Value::String(name.to_owned())), ("image", Value::String(image.to_owned()))])
+    }
+
+    mod merge_mode {
+        use super::*;
+
+        #[test]
+        fn it_parses_modes() {
+            assert_eq!(MergeMode::for_str("replace"), Ok(MergeMode::Replace));
+            assert_eq!(MergeMode::for_str("by-key:name"), Ok(MergeMode::ByKey("name".to_owned())));
+            assert_eq!(MergeMode::for_str("by-key:"), Err(()));
+            assert_eq!(MergeMode::for_str("unknown"), Err(()));
+        }
+    }
+
+    mod merge_values {
+        use super::*;
+
+        #[test]
+        fn it_merges_arrays() {
+            let left = Value::Array(vec![Value::Integer(1), Value::Integer(2)]);
+            let right = Value::Array(vec![Value::Integer(2), Value::Integer(3)]);
+
+            let mut strategy = MergeStrategy::default();
+            assert_eq!(merge_values(&left, &right, -1, &strategy), right);
+            strategy.arrays = MergeMode::Append;
+            assert_eq!(merge_values(&left, &right, -1, &strategy), Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(2), Value::Integer(3)]));
+            strategy.arrays = MergeMode::Union;
+            assert_eq!(merge_values(&left, &right, -1, &strategy), Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]));
+        }
+
+        #[test]
+        fn it_merges_arrays_by_key() {
+            let left = Value::Array(vec![new_container("app", "app:1"), new_container("proxy", "proxy:1")]);
+            let right = Value::Array(vec![new_container("proxy", "proxy:2"), new_container("log", "log:1")]);
+            let strategy = MergeStrategy {
+                arrays: MergeMode::ByKey("name".to_owned()),
+                paths: Vec::new(),
+            };
+
+            let res = merge_values(&left, &right, -1, &strategy);
+            assert_eq!(res, Value::Array(vec![new_container("app", "app:1"), new_container("proxy", "proxy:2"), new_container("log", "log:1")]));
+        }
+
+        #[test]
+        fn it_merges_paths() {
+            let left = new_object(&[
+                ("args", Value::Array(vec![Value::String("-v".to_owned())])),
+                ("env", new_object(&[("A", Value::Integer(1))])),
+            ]);
+            let right = new_object(&[
+                ("args", Value::Array(vec![Value::String("-q".to_owned())])),
+                ("env", new_object(&[("B", Value::Integer(2))])),
+            ]);
+            let strategy = MergeStrategy {
+                arrays: MergeMode::Merge,
+                paths: vec![(pointer_tokens("/args").unwrap(), MergeMode::Append), (pointer_tokens("/env").unwrap(), MergeMode::Replace)],
+            };
+
+            let res = merge_values(&left, &right, -1, &strategy);
+            assert_eq!(res, new_object(&[
+                ("args", Value::Array(vec![Value::String("-v".to_owned()), Value::String("-q".to_owned())])),
+                ("env", new_object(&[("B", Value::Integer(2))])),
+            ]));
+        }
+
+        #[test]
+        fn it_matches_wildcards() {
+            let left = Value::Array(vec![new_object(&[("ports", Value::Array(vec![Value::Integer(80)]))])]);
+            let right = Value::Array(vec![new_object(&[("ports", Value::Array(vec![Value::Integer(443)]))])]);
+            let strategy = MergeStrategy {
+                arrays: MergeMode::Merge,
+                paths: vec![(pointer_tokens("/*/ports").unwrap(), MergeMode::Union)],
+            };
+
+            let res = merge_values(&left, &right, -1, &strategy);
+            assert_eq!(res, Value::Array(vec![new_object(&[("ports", Value::Array(vec![Value::Integer(80), Value::Integer(443)]))])]));
+        }
+    }
+
+    mod merge_patch {
+        use super::*;
+
+        #[test]
+        fn it_applies_patches() {
+            let target = new_object(&[("a", Value::String("b".to_owned())), ("c", new_object(&[("d", Value::String("e".to_owned())), ("f", Value::String("g".to_owned()))]))]);
+            let patch = new_object(&[("a", Value::String("z".to_owned())), ("c", new_object(&[("f", Value::Nil)]))]);
+
+            let res = merge_patch(&target, &patch);
+            assert_eq!(res, new_object(&[("a", Value::String("z".to_owned())), ("c", new_object(&[("d", Value::String("e".to_owned()))]))]));
+        }
+
+        #[test]
+        fn it_replaces_non_objects() {
+            let target = new_object(&[("a", Value::Array(vec![Value::Integer(1)]))]);
+            let patch = new_object(&[("a", Value::Array(vec![Value::Integer(2)])), ("b", new_object(&[("c", Value::Nil)]))]);
+
+            let res = merge_patch(&target, &patch);
+            assert_eq!(res, new_object(&[("a", Value::Array(vec![Value::Integer(2)])), ("b", new_object(&[]))]));
+            assert_eq!(merge_patch(&target, &Value::Integer(1)), Value::Integer(1));
+        }
+    }
+
+    mod merge_patch_diff {
+        use super::*;
+
+        #[test]
+        fn it_creates_minimal_patches() {
+            let left = new_object(&[("a", Value::Integer(1)), ("b", new_object(&[("c", Value::Integer(2)), ("d", Value::Integer(3))])), ("e", Value::Integer(4))]);
+            let right = new_object(&[("a", Value::Integer(1)), ("b", new_object(&[("c", Value::Integer(5)), ("d", Value::Integer(3))])), ("f", Value::Integer(6))]);
+
+            let patch = merge_patch_diff(&left, &right).unwrap();
+            assert_eq!(patch, new_object(&[("e", Value::Nil), ("b", new_object(&[("c", Value::Integer(5))])), ("f", Value::Integer(6))]));
+            assert_eq!(merge_patch(&left, &patch), right);
+        }
+
+        #[test]
+        fn it_fails_on_nil_entries() {
+            let left = new_value(r#"{"a":{"b":1},"c":[1]}"#);
+
+            let res = merge_patch_diff(&left, &new_value(r#"{"a":{"b":null},"c":[1]}"#));
+            assert_eq!(res, Err("right document holds null at /a/b; not expressible as a merge patch".to_owned()));
+            let res = merge_patch_diff(&left, &new_value(r#"{"a":{"b":1},"c":[1],"d":{"e":null}}"#));
+            assert_eq!(res, Err("right document holds null at /d/e; not expressible as a merge patch".to_owned()));
+            // The Arrays are replaced as a whole.
+            let res = merge_patch_diff(&left, &new_value(r#"{"a":{"b":1},"c":[null]}"#));
+            assert_eq!(res, Ok(new_value(r#"{"c":[null]}"#)));
+        }
+    }
+    mod from_json_str {
+        use super::*;
+
+        #[test]
+        fn it_fails_on_overflowing_integers() {
+            assert_eq!(from_json_str(r#"{"a":[9223372036854775807,-1.5e300]}"#), Ok(new_value(r#"{"a":[9223372036854775807,-1.5e300]}"#)));
+            assert_eq!(from_json_str(r#"{"a":[1,9223372036854775808]}"#), Err("integer 9223372036854775808 overflows at '/a/1'".to_owned()));
+            assert_eq!(from_yaml_str("a:\n  b: 18446744073709551615\n"), Err("integer 18446744073709551615 overflows at '/a/b'".to_owned()));
+        }
+    }
+}
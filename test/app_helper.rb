@@ -76,20 +76,28 @@ module AppHelper
   # @param opts [Hash]
   # @option opts [IO] input
   # @option opts [IO] output
+  # @option opts [IO] error
+  # @option opts [Integer] status The expected exit status (0 by default).
   def self.exec_prog(args, opts = {})
     input = opts.fetch(:stdin, nil)
     use_stdin = !input.nil?
     output = opts.fetch(:stdout, nil)
     use_stdout = !output.nil?
+    error = opts.fetch(:stderr, nil)
+    use_stderr = !error.nil?
+    expected_status = opts.fetch(:status, 0)
 
     capture_opts = {}
     capture_opts[:stdin_data] = input.string if use_stdin
-    stdout_data, exit_status = Open3.capture2(AppHelper.program, *args, capture_opts)
+    stdout_data, stderr_data, exit_status = Open3.capture3(AppHelper.program, *args, capture_opts)
     if use_stdout
       output.write stdout_data
     end
+    if use_stderr
+      error.write stderr_data
+    end
 
-    raise RuntimeError, "exit_status: actual(#{exit_status}) != expected(0)" if exit_status != 0
+    raise RuntimeError, "exit_status: actual(#{exit_status.exitstatus}) != expected(#{expected_status})" if exit_status.exitstatus != expected_status
 
     exit_status
   end
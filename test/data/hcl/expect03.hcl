@@ -0,0 +1,14 @@
+region = "eu-west-1"
+ami = var.ami
+
+tags {
+  env = "prod"
+}
+
+service {
+  port = 80
+}
+
+service {
+  port = 443
+}
@@ -0,0 +1,14 @@
+region = "eu-west-1"
+ami    = var.ami
+name   = "${var.prefix}-web"
+
+resource "aws_instance" "web" {
+  count = 2
+}
+
+service {
+  port = 80
+}
+service {
+  port = 443
+}
@@ -0,0 +1,4 @@
+Object:new({
+    { "name", "cache" },
+    { "db", ctx:include("db.json") },
+})
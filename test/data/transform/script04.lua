@@ -0,0 +1,3 @@
+local val = ctx:get_input(1)
+val:set("name", nmae)
+ctx:set_output(val)
@@ -0,0 +1,46 @@
+require_relative '../app_helper'
+
+describe 'canonical' do
+  context = AppHelper.new_context('canonical')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'canonicalize to YAML file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'sorts keys' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--canonicalize", "#{AppHelper.tmpdir}/out.yaml"]
+      expect(File.read("#{AppHelper.tmpdir}/out.yaml")).to eq(File.read("#{context.datadir}/expect02.yaml"))
+    end
+  end
+
+  describe 'copy to canonical JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes canonical JSON' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--copy", "--json", "--canonical", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+  end
+
+  describe 'digest to stdout' do
+    it 'hashes the canonical form' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input02.yaml", "--digest", "sha256", "--plain", "--eol", "-", "--plain", "--eol", "-"], :stdout => sb
+      lines = sb.string.lines
+      expect(lines.length).to eq(2)
+      expect(lines[0]).to match(/\A[0-9a-f]{64}\n\z/)
+      expect(lines[1]).to eq(lines[0])
+    end
+  end
+end
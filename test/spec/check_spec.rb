@@ -0,0 +1,74 @@
+require 'json'
+require_relative '../app_helper'
+
+describe 'check' do
+  context = AppHelper.new_context('check')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'check inputs' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'accepts sound documents' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input02.yaml", "#{context.datadir}/input04.yaml", "--check"], :stderr => se, :status => 0
+      expect(se.string).to eq("")
+    end
+
+    it 'reports overflowing integers' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--check"], :stderr => se, :status => 21
+      expect(se.string).to eq("#{context.datadir}/input01.json: integer 9223372036854775808 overflows at '/replicas'\n")
+    end
+
+    it 'reads the inputs with their options' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--yaml", "--include", "#{context.datadir}/../include/input02.yaml", "--check"], :stderr => se, :status => 21
+      expect(se.string).to include("include cycle ")
+    end
+
+    it 'reports every wrong document' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input03.json", "#{context.datadir}/input04.yaml", "#{context.datadir}/missing.json", "--check"], :stderr => se, :status => 21
+      expect(se.string.lines.length).to eq(2)
+      expect(se.string).to include("#{context.datadir}/input03.json: ")
+      expect(se.string).to include("#{context.datadir}/missing.json: ")
+    end
+
+    it 'reports strict problems' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input02.yaml", "--check", "--strict"], :stderr => se, :status => 21
+      expect(se.string).to include("input01.json: duplicate key at '/name'\n")
+      expect(se.string).to include("input01.json: integer 9223372036854775808 overflows at '/replicas'\n")
+      expect(se.string).to include("input02.yaml: non-string key 1 at '/1'\n")
+      expect(se.string).to include("input02.yaml: NaN at '/ratio'\n")
+    end
+
+    it 'reports as JSON' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input04.yaml", "--check", "--strict", "--report", "json"], :stdout => sb, :status => 21
+      report = JSON.parse(sb.string)
+      expect(report.length).to eq(2)
+      expect(report[0]['problems'].length).to eq(2)
+      expect(report[1]['problems']).to eq([])
+    end
+
+    it 'exits after other commands' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.yaml", "#{context.datadir}/input04.yaml", "--merge", "--check"], :status => 0
+    end
+
+    it 'rejects options after other commands' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--copy", "--check", "--strict"], :stderr => se, :status => 10
+      expect(se.string).to eq("check options require check to be the first command\n")
+    end
+  end
+end
@@ -0,0 +1,46 @@
+require_relative '../app_helper'
+
+describe 'diff' do
+  context = AppHelper.new_context('diff')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'diff to plain file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'lists differences' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.toml", "#{context.datadir}/input02.yaml", "--diff", "--plain", "--eol", "#{AppHelper.tmpdir}/out.txt"], :status => 40
+      expect(File.read("#{AppHelper.tmpdir}/out.txt")).to eq(File.read("#{context.datadir}/expect01.txt"))
+    end
+
+    it 'ignores key and array order' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.toml", "#{context.datadir}/input03.json", "--diff", "--ignore-key-order", "--ignore-array-order", "--plain", "-"], :stdout => sb
+      expect(sb.string).to eq('')
+    end
+  end
+
+  describe 'diff to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'creates a JSON patch' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.toml", "#{context.datadir}/input02.yaml", "--diff", "--ignore-key-order", "--format", "patch", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"], :status => 40
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect02.json"))
+    end
+
+    it 'creates a JSON merge patch' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.toml", "#{context.datadir}/input02.yaml", "--diff", "--ignore-key-order", "--format=merge-patch", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"], :status => 40
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect03.json"))
+    end
+  end
+end
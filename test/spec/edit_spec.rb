@@ -0,0 +1,30 @@
+require_relative '../app_helper'
+
+describe 'edit' do
+  context = AppHelper.new_context('edit')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'edit to YAML file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'sets, deletes and renames' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--set", "image.tag:S=1.2", "--delete", "secret", "--rename", "image=container", "--set", "/resources/limits/cpu=0.5", "--yaml", "#{AppHelper.tmpdir}/out.yaml"]
+      expect(File.read("#{AppHelper.tmpdir}/out.yaml")).to eq(File.read("#{context.datadir}/expect01.yaml"))
+    end
+
+    it 'fails on a scalar intermediate value' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--set", "image.name.first=x", "--yaml", "#{AppHelper.tmpdir}/out.yaml"], :stderr => se, :status => 21
+      expect(se.string).to eq("set: '/image/name' is not a container\n")
+    end
+  end
+end
@@ -0,0 +1,51 @@
+require_relative '../app_helper'
+
+describe 'env and properties' do
+  context = AppHelper.new_context('env_properties')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'read env and properties files' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reads an env file' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.env", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'reads a properties file' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.properties", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect02.json"))
+    end
+  end
+
+  describe 'write env and properties files' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes a flattened env file' do
+      AppHelper.exec_prog ["#{context.datadir}/input03.yaml", "--copy", "--env", "--separator", "_", "#{AppHelper.tmpdir}/out.env"]
+      expect(File.read("#{AppHelper.tmpdir}/out.env")).to eq(File.read("#{context.datadir}/expect03.env"))
+    end
+
+    it 'writes a flattened properties file' do
+      AppHelper.exec_prog ["#{context.datadir}/input03.yaml", "--copy", "--properties", "--separator=.", "#{AppHelper.tmpdir}/out.properties"]
+      expect(File.read("#{AppHelper.tmpdir}/out.properties")).to eq(File.read("#{context.datadir}/expect04.properties"))
+    end
+
+    it 'fails on nested values' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input03.yaml", "--copy", "--env", "#{AppHelper.tmpdir}/out.env"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: nested value at 'db'\n")
+    end
+  end
+end
@@ -0,0 +1,40 @@
+require_relative '../app_helper'
+
+describe 'flatten' do
+  context = AppHelper.new_context('flatten')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'flatten to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'flattens with indices' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--flatten", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'flattens with brackets' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--flatten", "--separator", "__", "--array-style", "brackets", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect02.json"))
+    end
+  end
+
+  describe 'unflatten to YAML file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reverses flatten' do
+      AppHelper.exec_prog ["#{context.datadir}/expect02.json", "--unflatten", "--separator=__", "--array-style=brackets", "--yaml", "#{AppHelper.tmpdir}/out.yaml"]
+      expect(File.read("#{AppHelper.tmpdir}/out.yaml")).to eq(File.read("#{context.datadir}/input01.yaml"))
+    end
+  end
+end
@@ -0,0 +1,56 @@
+require_relative '../app_helper'
+
+describe 'frontmatter' do
+  context = AppHelper.new_context('frontmatter')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'read front matter file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reads the meta and the body' do
+      AppHelper.exec_prog ["--frontmatter", "#{context.datadir}/input01.md", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+  end
+
+  describe 'write front matter file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes the edited meta and the same body' do
+      AppHelper.exec_prog ["--frontmatter", "#{context.datadir}/input01.md", "--set", "meta.draft=false", "--frontmatter", "#{AppHelper.tmpdir}/out.md"]
+      expect(File.read("#{AppHelper.tmpdir}/out.md")).to eq(File.read("#{context.datadir}/expect02.md"))
+    end
+
+    it 'writes TOML meta' do
+      AppHelper.exec_prog ["--frontmatter", "#{context.datadir}/input02.md", "--copy", "--frontmatter", "--meta", "toml", "#{AppHelper.tmpdir}/out.md"]
+      expect(File.read("#{AppHelper.tmpdir}/out.md")).to eq(File.read("#{context.datadir}/expect03.md"))
+    end
+
+    it 'keeps the TOML meta' do
+      AppHelper.exec_prog ["--frontmatter", "#{context.datadir}/input02.md", "--copy", "--frontmatter", "#{AppHelper.tmpdir}/out.md"]
+      expect(File.read("#{AppHelper.tmpdir}/out.md")).to eq(File.read("#{context.datadir}/expect03.md"))
+    end
+
+    it 'keeps a header without entries' do
+      AppHelper.exec_prog ["--frontmatter", "#{context.datadir}/input03.md", "--copy", "--frontmatter", "#{AppHelper.tmpdir}/out.md"]
+      expect(File.read("#{AppHelper.tmpdir}/out.md")).to eq(File.read("#{context.datadir}/expect04.md"))
+    end
+
+    it 'fails on non-object documents' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--document", "json", '[1]', "--copy", "--frontmatter", "#{AppHelper.tmpdir}/out.md"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: frontmatter requires an object\n")
+    end
+  end
+end
@@ -0,0 +1,46 @@
+require_relative '../app_helper'
+
+describe 'hcl' do
+  context = AppHelper.new_context('hcl')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'read HCL file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reads attributes and blocks' do
+      AppHelper.exec_prog ["--hcl", "#{context.datadir}/input01.tf", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+  end
+
+  describe 'write HCL file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes attributes' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.yaml", "--copy", "#{AppHelper.tmpdir}/out.tfvars"]
+      expect(File.read("#{AppHelper.tmpdir}/out.tfvars")).to eq(File.read("#{context.datadir}/expect02.tfvars"))
+    end
+
+    it 'writes blocks' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.yaml", "--copy", "--hcl", "--blocks", "#{AppHelper.tmpdir}/out.hcl"]
+      expect(File.read("#{AppHelper.tmpdir}/out.hcl")).to eq(File.read("#{context.datadir}/expect03.hcl"))
+    end
+
+    it 'fails on non-object documents' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--document", "json", '[1]', "--copy", "--hcl", "#{AppHelper.tmpdir}/out.hcl"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: hcl requires an object\n")
+    end
+  end
+end
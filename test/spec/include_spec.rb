@@ -0,0 +1,47 @@
+require_relative '../app_helper'
+
+describe 'include' do
+  context = AppHelper.new_context('include')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'include files to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'expands nested includes' do
+      AppHelper.exec_prog ["--yaml", "--include", "#{context.datadir}/input01.yaml", "--copy", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'does not expand includes by default' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--copy", "--json", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect02.json"))
+    end
+
+    it 'detects cycles' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--yaml", "--include", "#{context.datadir}/input02.yaml", "--copy", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to include("include cycle")
+    end
+
+    it 'rejects absolute paths' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--yaml", "--include", "-", "--copy", "--json", "#{AppHelper.tmpdir}/out.json"], :stdin => StringIO.new("---\na: !include /etc/hosts\n"), :stderr => se, :status => 21
+      expect(se.string).to eq("-: cannot include '/etc/hosts': absolute path\n")
+    end
+
+    it 'rejects paths out of the input directory' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--yaml", "--include", "#{context.datadir}/sub/escape.yaml", "--copy", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to include("input01.yaml': outside of '")
+    end
+  end
+end
@@ -0,0 +1,28 @@
+require_relative '../app_helper'
+
+describe 'infer-schema' do
+  context = AppHelper.new_context('infer_schema')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'infer schema to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'infers' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input02.yaml", "#{context.datadir}/input03.toml", "--infer-schema", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'validates the samples' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input02.yaml", "#{context.datadir}/input03.toml", "--validate", "#{context.datadir}/expect01.json", "--json", "#{AppHelper.tmpdir}/out1.json", "--json", "#{AppHelper.tmpdir}/out2.json", "--json", "#{AppHelper.tmpdir}/out3.json"]
+    end
+  end
+end
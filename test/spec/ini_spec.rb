@@ -0,0 +1,46 @@
+require_relative '../app_helper'
+
+describe 'ini' do
+  context = AppHelper.new_context('ini')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'read INI file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reads strings' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.ini", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'reads typed values and repeated keys' do
+      AppHelper.exec_prog ["--ini", "--typed", "--repeated-keys=array", "#{context.datadir}/input01.ini", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect02.json"))
+    end
+  end
+
+  describe 'write INI file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes sections' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.yaml", "--copy", "--ini", "--typed", "#{AppHelper.tmpdir}/out.ini"]
+      expect(File.read("#{AppHelper.tmpdir}/out.ini")).to eq(File.read("#{context.datadir}/expect03.ini"))
+    end
+
+    it 'fails on deep values' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--document", "json", '{"a":{"b":{"c":{"d":1}}}}', "--copy", "--ini", "#{AppHelper.tmpdir}/out.ini"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: nested value at '/a/b/c'\n")
+    end
+  end
+end
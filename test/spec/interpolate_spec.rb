@@ -0,0 +1,31 @@
+require_relative '../app_helper'
+
+describe 'interpolate' do
+  context = AppHelper.new_context('interpolate')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'interpolate to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'expands and retypes variables' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--interpolate", "--vars", "#{context.datadir}/vars01.yaml", "--retype", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'fails on missing variables in strict mode' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--interpolate", "--vars", "#{context.datadir}/vars02.yaml", "--strict", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to eq("document 0: '/db/url': undefined variable 'DB_NAME'\n")
+      expect(File.exist?("#{AppHelper.tmpdir}/out.json")).to be false
+    end
+  end
+end
@@ -118,4 +118,40 @@ describe 'merge' do
       expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect06.json"))
     end
   end
+
+  describe 'merge to JSON file (strategies)' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'merges (arrays replace)' do
+      AppHelper.exec_prog ["#{context.datadir}/input07.yaml", "#{context.datadir}/input08.yaml", "--merge", "--arrays=replace", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect13.json"))
+    end
+
+    it 'merges (arrays by key, with paths)' do
+      AppHelper.exec_prog ["#{context.datadir}/input07.yaml", "#{context.datadir}/input08.yaml", "--merge", "--arrays", "by-key:name", "--strategy", "/containers/*/ports=union", "--strategy", "/args=append", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect14.json"))
+    end
+
+    it 'fails with a wrong mode' do
+      AppHelper.exec_prog ["#{context.datadir}/input07.yaml", "#{context.datadir}/input08.yaml", "--merge", "--arrays=zip", "--json", "#{AppHelper.tmpdir}/out.json"], :status => 10
+    end
+  end
+
+  describe 'merge to JSON file (patch)' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'merges patches' do
+      AppHelper.exec_prog ["#{context.datadir}/input09.yaml", "#{context.datadir}/input10.yaml", "--merge", "--patch", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect15.json"))
+    end
+
+    it 'creates merge patches' do
+      AppHelper.exec_prog ["#{context.datadir}/input09.yaml", "#{context.datadir}/expect15.json", "--merge-patch-diff", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect16.json"))
+    end
+  end
 end
@@ -0,0 +1,31 @@
+require_relative '../app_helper'
+
+describe 'patch' do
+  context = AppHelper.new_context('patch')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'patch to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'patches' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "#{context.datadir}/patch01.json", "--json-patch", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'fails on a failing test operation' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "#{context.datadir}/patch02.json", "--json-patch", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to eq("patch: operation 1: test failed at '/image/tag'\n")
+      expect(File.exist?("#{AppHelper.tmpdir}/out.json")).to be false
+    end
+  end
+end
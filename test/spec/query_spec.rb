@@ -0,0 +1,52 @@
+require_relative '../app_helper'
+
+describe 'query' do
+  context = AppHelper.new_context('query')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'query to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'selects a value' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--query", ".spec.template.containers[0].image", "--json", "-"], :stdout => sb
+      expect(sb.string).to eq('["app:1.0"]')
+    end
+
+    it 'selects descendants' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--query", "$..containerPort", "--json", "-"], :stdout => sb
+      expect(sb.string).to eq('[8080,80,443]')
+    end
+
+    it 'filters' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--query", "$..containers[?@.ports[1]]", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'fails on a wrong query' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--query", "$.spec[", "--json", "-"], :status => 10
+    end
+  end
+
+  describe 'query each to plain files' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'selects values as documents' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "--query-each", "$.spec.template.containers[*].name", "--plain", "#{AppHelper.tmpdir}/out1.txt", "--plain", "#{AppHelper.tmpdir}/out2.txt"]
+      expect(File.read("#{AppHelper.tmpdir}/out1.txt")).to eq('app')
+      expect(File.read("#{AppHelper.tmpdir}/out2.txt")).to eq('proxy')
+    end
+  end
+end
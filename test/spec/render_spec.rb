@@ -123,4 +123,113 @@ EOS
       expect(sb.string).to eq(expect)
     end
   end
+
+  describe 'render a template in strict mode' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--strict", "#{context.datadir}/template01.txt", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect01.txt"))
+    end
+
+    it 'fails on a missing key' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--strict", "#{context.datadir}/template08.txt", "-"], :stderr => se, :status => 21
+      expect(se.string).to eq("#{context.datadir}/template08.txt:4: undefined key 'scroe'\n")
+    end
+
+    it 'fails on an undefined variable' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--strict", "#{context.datadir}/template09.txt", "-"], :stderr => se, :status => 21
+      expect(se.string).to eq("#{context.datadir}/template09.txt:4: undefined variable 'sorce'\n")
+    end
+  end
+
+  describe 'render a template once per document' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders to files' do
+      AppHelper.exec_prog ["#{context.datadir}/input02.json", "--unpack", "--render-each", "#{context.datadir}/template10.txt", "#{AppHelper.tmpdir}/out1.txt", "#{AppHelper.tmpdir}/out2.txt"]
+      expect(File.read("#{AppHelper.tmpdir}/out1.txt")).to eq(File.read("#{context.datadir}/expect02.txt"))
+      expect(File.read("#{AppHelper.tmpdir}/out2.txt")).to eq(File.read("#{context.datadir}/expect03.txt"))
+    end
+
+    it 'renders to a stream' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input02.json", "--unpack", "--render-each", "#{context.datadir}/template10.txt", "--plain", "--stream", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect02.txt") + File.read("#{context.datadir}/expect03.txt"))
+    end
+  end
+
+  describe 'render a template as a document' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--as", "yaml", "#{context.datadir}/template11.txt", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect04.json"))
+    end
+
+    it 'fails on a parse error' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--as=yaml", "#{context.datadir}/template12.txt", "--json", "-"], :stderr => se, :status => 21
+      expect(se.string).to start_with("#{context.datadir}/template12.txt: wrong rendered document:")
+    end
+  end
+
+  describe 'render a template with special characters' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "#{context.datadir}/template13.txt", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect05.txt"))
+    end
+  end
+
+  describe 'render a compiled template' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders' do
+      AppHelper.exec_prog ["--compile-template", "#{context.datadir}/template01.txt", "--plain", "#{AppHelper.tmpdir}/template.luac"]
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "#{AppHelper.tmpdir}/template.luac", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect01.txt"))
+    end
+
+    it 'reports errors against the template' do
+      AppHelper.exec_prog ["--compile-template", "#{context.datadir}/template08.txt", "--plain", "#{AppHelper.tmpdir}/template.luac"]
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "--strict", "#{AppHelper.tmpdir}/template.luac", "-"], :stderr => se, :status => 21
+      expect(se.string).to eq("#{context.datadir}/template08.txt:4: undefined key 'scroe'\n")
+    end
+  end
+
+  describe 'render a jinja template' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'renders (extension)' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--render", "#{context.datadir}/template14.j2", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect01.txt"))
+    end
+
+    it 'renders once per document (engine option)' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input02.json", "--unpack", "--render-each", "--engine", "jinja", "#{context.datadir}/template15.txt", "--plain", "--stream", "-"], :stdout => sb
+      expect(sb.string).to eq(File.read("#{context.datadir}/expect02.txt") + File.read("#{context.datadir}/expect03.txt"))
+    end
+  end
 end
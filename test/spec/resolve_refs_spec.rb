@@ -0,0 +1,30 @@
+require_relative '../app_helper'
+
+describe 'resolve-refs' do
+  context = AppHelper.new_context('resolve_refs')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'resolve refs to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'resolves references' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.yaml", "#{context.datadir}/input02.json", "--resolve-refs", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out1.json", "--json", "#{AppHelper.tmpdir}/out2.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out1.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'detects cycles' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input03.yaml", "--resolve-refs", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to eq("document 0: reference cycle 0#/b -> 0#/a -> 0#/b\n")
+    end
+  end
+end
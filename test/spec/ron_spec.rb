@@ -0,0 +1,47 @@
+require_relative '../app_helper'
+
+describe 'ron' do
+  context = AppHelper.new_context('ron')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'read RON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'reads structs, variants and options' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.ron", "--copy", "--json", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'fails on wrong documents' do
+      si = StringIO.new("(a: [1 2])")
+      se = StringIO.new
+      AppHelper.exec_prog ["--ron", "-", "--copy", "--json", "#{AppHelper.tmpdir}/out.json"], :stdin => si, :stderr => se, :status => 21
+      expect(se.string).to eq("-: line 1: expected ']'\n")
+    end
+  end
+
+  describe 'write RON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes compact documents' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.ron", "--copy", "--ron", "--eol", "#{AppHelper.tmpdir}/out.ron"]
+      expect(File.read("#{AppHelper.tmpdir}/out.ron")).to eq(File.read("#{context.datadir}/expect02.ron"))
+    end
+
+    it 'writes pretty documents' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.ron", "--copy", "--ron", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.ron"]
+      expect(File.read("#{AppHelper.tmpdir}/out.ron")).to eq(File.read("#{context.datadir}/expect03.ron"))
+    end
+  end
+end
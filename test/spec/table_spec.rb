@@ -0,0 +1,48 @@
+require_relative '../app_helper'
+
+describe 'table' do
+  context = AppHelper.new_context('table')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'write table file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'writes ASCII tables' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--copy", "--table", "#{AppHelper.tmpdir}/out.txt"]
+      expect(File.read("#{AppHelper.tmpdir}/out.txt")).to eq(File.read("#{context.datadir}/expect01.txt"))
+    end
+
+    it 'writes Markdown tables with selected columns' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--copy", "--markdown-table", "--columns", "version,name", "#{AppHelper.tmpdir}/out.md"]
+      expect(File.read("#{AppHelper.tmpdir}/out.md")).to eq(File.read("#{context.datadir}/expect02.md"))
+    end
+
+    it 'writes HTML tables' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--copy", "--html-table", "#{AppHelper.tmpdir}/out.html"]
+      expect(File.read("#{AppHelper.tmpdir}/out.html")).to eq(File.read("#{context.datadir}/expect03.html"))
+    end
+
+    it 'fails on non-array documents' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--document", "json", '{"a":1}', "--copy", "--table", "#{AppHelper.tmpdir}/out.txt"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: table requires an array of objects\n")
+    end
+  end
+
+  describe 'write plain file' do
+    it 'fails on non-scalar documents' do
+      se = StringIO.new
+      AppHelper.exec_prog ["--document", "json", '[1]', "--copy", "--plain", "#{AppHelper.tmpdir}/out.txt"], :stderr => se, :status => 31
+      expect(se.string).to eq("wrong output: plain requires a scalar\n")
+    end
+  end
+end
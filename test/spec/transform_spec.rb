@@ -45,4 +45,22 @@ describe 'transform' do
       expect(sb.string).to eq('{"name":"Bernard"}')
     end
   end
+
+  describe 'transform 1 object in strict mode' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'transforms' do
+      sb = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--transform", "--strict", "#{context.datadir}/script03.lua", "--json", "-"], :stdout => sb
+      expect(sb.string).to eq('{"name":"Bernard"}')
+    end
+
+    it 'fails on an undefined variable' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--transform", "--strict", "#{context.datadir}/script04.lua", "--json", "-"], :stderr => se, :status => 21
+      expect(se.string).to eq("#{context.datadir}/script04.lua:2: undefined variable 'nmae'\n")
+    end
+  end
 end
@@ -0,0 +1,39 @@
+require_relative '../app_helper'
+
+describe 'validate' do
+  context = AppHelper.new_context('validate')
+
+  before :all do
+    AppHelper.make_dir(AppHelper.tmpdir)
+  end
+
+  after :all do
+    AppHelper.clear_dir(AppHelper.tmpdir)
+  end
+
+  describe 'validate to JSON file' do
+    before :each do
+      AppHelper.clear_dir(AppHelper.tmpdir)
+    end
+
+    it 'validates and fills defaults' do
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--validate", "--fill-defaults", "#{context.datadir}/schema01.yaml", "--json", "--pretty", "--eol", "#{AppHelper.tmpdir}/out.json"]
+      expect(File.read("#{AppHelper.tmpdir}/out.json")).to eq(File.read("#{context.datadir}/expect01.json"))
+    end
+
+    it 'lists every violation' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "#{context.datadir}/input02.toml", "--validate", "#{context.datadir}/schema01.yaml", "--json", "#{AppHelper.tmpdir}/out1.json", "--json", "#{AppHelper.tmpdir}/out2.json"], :stderr => se, :status => 21
+      expect(se.string.lines.length).to eq(4)
+      expect(se.string).to include("document 1: '/replicas': 0 is less than the minimum of 1 (schema '/properties/replicas/minimum')\n")
+      expect(File.exist?("#{AppHelper.tmpdir}/out1.json")).to be false
+    end
+
+    it 'fails on a missing schema' do
+      se = StringIO.new
+      AppHelper.exec_prog ["#{context.datadir}/input01.json", "--validate", "#{context.datadir}/missing.yaml", "--json", "#{AppHelper.tmpdir}/out.json"], :stderr => se, :status => 21
+      expect(se.string).to start_with("#{context.datadir}/missing.yaml: ")
+      expect(File.exist?("#{AppHelper.tmpdir}/out.json")).to be false
+    end
+  end
+end